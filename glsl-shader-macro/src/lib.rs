@@ -0,0 +1,552 @@
+//! A macro for writing shader functions with a GLSL-like syntax.
+//!
+//! ```rust,ignore
+//! let main = shader! {
+//!     fn main() {
+//!         mat4 worldview = global.view * global.camera;
+//!         gl_Position = global.projection * worldview * model.model * vec4(position, 1.0);
+//!         v_tex_coord = tex_coord;
+//!     }
+//! };
+//!
+//! shader.entry_point_auto(ShaderKind::Vertex, main.returns_void())?;
+//! ```
+//!
+//! Identifiers refer to Rust bindings of variables (`InputVar`, `UniformVar`, ...) which are in
+//! scope where the macro is used. Members of uniform blocks are accessed by name.
+//!
+//! The macro expands to calls to `::spvc_shader::FunctionBuilder`, and evaluates to the builder.
+//! Operations which fail to type check are attributed to the operator or function that
+//! constructed them, so that errors reported when building the shader point at the offending
+//! token.
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use std::iter::FromIterator;
+
+/// An error while parsing the body of the macro.
+struct Error {
+    span: Span,
+    message: String,
+}
+
+impl Error {
+    fn new<M: Into<String>>(span: Span, message: M) -> Error {
+        Error {
+            span: span,
+            message: message.into(),
+        }
+    }
+
+    /// Convert into an invocation of `compile_error!`, pointing at the offending token.
+    fn into_tokens(self) -> TokenStream {
+        let message = TokenTree::Literal(Literal::string(&self.message));
+        let mut args = Group::new(Delimiter::Parenthesis, TokenStream::from(message));
+        args.set_span(self.span);
+        let mut out = code("compile_error!", self.span);
+        out.extend(Some(TokenTree::Group(args)));
+        out
+    }
+}
+
+type Result<T> = ::std::result::Result<T, Error>;
+
+#[derive(Debug)]
+enum Expr {
+    /// Reference to a binding in scope.
+    Var(Ident),
+    /// A literal number, possibly negated.
+    Lit(Literal, bool),
+    /// Access of a struct member.
+    Member(Box<Expr>, Ident),
+    /// A call to a built-in function.
+    Call(Ident, Vec<Expr>),
+    /// A binary operation.
+    Binary(char, Span, Box<Expr>, Box<Expr>),
+    /// Negation of an expression.
+    Neg(Span, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Stmt {
+    /// A local binding.
+    Let(Ident, Expr),
+    /// Store into a variable.
+    Assign(Expr, Span, Expr),
+}
+
+struct Parser {
+    tokens: Vec<TokenTree>,
+    pos: usize,
+    /// Span used when reporting errors at the end of input.
+    end: Span,
+}
+
+impl Parser {
+    fn new(stream: TokenStream, end: Span) -> Parser {
+        Parser {
+            tokens: stream.into_iter().collect(),
+            pos: 0,
+            end: end,
+        }
+    }
+
+    fn peek(&self) -> Option<&TokenTree> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, n: usize) -> Option<&TokenTree> {
+        self.tokens.get(self.pos + n)
+    }
+
+    fn next(&mut self) -> Result<TokenTree> {
+        let token = self.tokens.get(self.pos).cloned().ok_or_else(|| {
+            Error::new(self.end, "unexpected end of input")
+        })?;
+
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn span(&self) -> Span {
+        self.peek().map(|t| t.span()).unwrap_or(self.end)
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        match self.peek() {
+            Some(&TokenTree::Punct(ref p)) => p.as_char() == c,
+            _ => false,
+        }
+    }
+
+    fn is_ident(&self, name: &str) -> bool {
+        match self.peek() {
+            Some(&TokenTree::Ident(ref i)) => i.to_string() == name,
+            _ => false,
+        }
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<Span> {
+        match self.next()? {
+            TokenTree::Punct(ref p) if p.as_char() == c => Ok(p.span()),
+            other => Err(Error::new(other.span(), format!("expected `{}`", c))),
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<Ident> {
+        match self.next()? {
+            TokenTree::Ident(ident) => Ok(ident),
+            other => Err(Error::new(other.span(), "expected identifier")),
+        }
+    }
+
+    fn expect_group(&mut self, delimiter: Delimiter) -> Result<Group> {
+        match self.next()? {
+            TokenTree::Group(ref g) if g.delimiter() == delimiter => Ok(g.clone()),
+            other => Err(Error::new(other.span(), "unexpected token")),
+        }
+    }
+
+    /// Parse `fn <name>() { <stmts> }`.
+    fn function(&mut self) -> Result<(Ident, Vec<Stmt>)> {
+        if !self.is_ident("fn") {
+            return Err(Error::new(self.span(), "expected `fn`"));
+        }
+
+        self.next()?;
+        let name = self.expect_ident()?;
+
+        let args = self.expect_group(Delimiter::Parenthesis)?;
+
+        if !args.stream().is_empty() {
+            return Err(Error::new(args.span(), "functions can't have arguments"));
+        }
+
+        let body = self.expect_group(Delimiter::Brace)?;
+
+        if !self.is_empty() {
+            return Err(Error::new(self.span(), "unexpected token after function"));
+        }
+
+        let mut parser = Parser::new(body.stream(), body.span());
+        let mut stmts = Vec::new();
+
+        while !parser.is_empty() {
+            stmts.push(parser.stmt()?);
+        }
+
+        Ok((name, stmts))
+    }
+
+    fn stmt(&mut self) -> Result<Stmt> {
+        // `let <name> = <expr>;`, or `<type> <name> = <expr>;`
+        let is_decl = self.is_ident("let") ||
+            match (self.peek(), self.peek_at(1)) {
+                (Some(&TokenTree::Ident(_)), Some(&TokenTree::Ident(_))) => true,
+                _ => false,
+            };
+
+        if is_decl {
+            self.next()?;
+            let name = self.expect_ident()?;
+            self.expect_punct('=')?;
+            let expr = self.expr()?;
+            self.expect_punct(';')?;
+            return Ok(Stmt::Let(name, expr));
+        }
+
+        let place = self.expr()?;
+        let span = self.expect_punct('=')?;
+        let expr = self.expr()?;
+        self.expect_punct(';')?;
+
+        match place {
+            Expr::Var(..) | Expr::Member(..) => Ok(Stmt::Assign(place, span, expr)),
+            _ => Err(Error::new(span, "can only assign to variables and members")),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        let mut lhs = self.term()?;
+
+        while self.is_punct('+') || self.is_punct('-') {
+            let (c, span) = self.op()?;
+            let rhs = self.term()?;
+            lhs = Expr::Binary(c, span, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        let mut lhs = self.unary()?;
+
+        while self.is_punct('*') {
+            let (c, span) = self.op()?;
+            let rhs = self.unary()?;
+            lhs = Expr::Binary(c, span, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    fn op(&mut self) -> Result<(char, Span)> {
+        match self.next()? {
+            TokenTree::Punct(ref p) => Ok((p.as_char(), p.span())),
+            other => Err(Error::new(other.span(), "expected operator")),
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.is_punct('-') {
+            let span = self.expect_punct('-')?;
+
+            return Ok(match self.unary()? {
+                Expr::Lit(lit, negative) => Expr::Lit(lit, !negative),
+                expr => Expr::Neg(span, Box::new(expr)),
+            });
+        }
+
+        let mut expr = self.primary()?;
+
+        while self.is_punct('.') {
+            self.next()?;
+            let member = self.expect_ident()?;
+            expr = Expr::Member(Box::new(expr), member);
+        }
+
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        match self.next()? {
+            TokenTree::Ident(ident) => {
+                let is_call = match self.peek() {
+                    Some(&TokenTree::Group(ref g)) => g.delimiter() == Delimiter::Parenthesis,
+                    _ => false,
+                };
+
+                if !is_call {
+                    return Ok(Expr::Var(ident));
+                }
+
+                let args = self.expect_group(Delimiter::Parenthesis)?;
+                let mut parser = Parser::new(args.stream(), args.span());
+                let mut out = Vec::new();
+
+                while !parser.is_empty() {
+                    out.push(parser.expr()?);
+
+                    if !parser.is_empty() {
+                        parser.expect_punct(',')?;
+                    }
+                }
+
+                Ok(Expr::Call(ident, out))
+            }
+            TokenTree::Literal(lit) => Ok(Expr::Lit(lit, false)),
+            TokenTree::Group(ref g) if g.delimiter() == Delimiter::Parenthesis => {
+                let mut parser = Parser::new(g.stream(), g.span());
+                let expr = parser.expr()?;
+
+                if !parser.is_empty() {
+                    return Err(Error::new(parser.span(), "expected `)`"));
+                }
+
+                Ok(expr)
+            }
+            other => Err(Error::new(other.span(), "expected expression")),
+        }
+    }
+}
+
+/// Set the span of every token in the stream.
+fn respan(stream: TokenStream, span: Span) -> TokenStream {
+    stream
+        .into_iter()
+        .map(|mut token| {
+            if let TokenTree::Group(ref g) = token {
+                let mut group = Group::new(g.delimiter(), respan(g.stream(), span));
+                group.set_span(span);
+                return TokenTree::Group(group);
+            }
+
+            token.set_span(span);
+            token
+        })
+        .collect()
+}
+
+/// Parse the given code, with every token pointing at the given span.
+fn code(code: &str, span: Span) -> TokenStream {
+    respan(code.parse().expect("bad generated code"), span)
+}
+
+fn punct(c: char, span: Span) -> TokenTree {
+    let mut punct = Punct::new(c, Spacing::Alone);
+    punct.set_span(span);
+    TokenTree::Punct(punct)
+}
+
+/// Generate a call to the given function.
+fn call(path: &str, args: Vec<TokenStream>, span: Span) -> TokenStream {
+    let mut inner = TokenStream::new();
+
+    for (index, arg) in args.into_iter().enumerate() {
+        if index > 0 {
+            inner.extend(Some(punct(',', span)));
+        }
+
+        inner.extend(arg);
+    }
+
+    let mut group = Group::new(Delimiter::Parenthesis, inner);
+    group.set_span(span);
+
+    let mut out = code(path, span);
+    out.extend(Some(TokenTree::Group(group)));
+    out
+}
+
+/// Attribute the operation to the given span, if it fails to type check.
+fn at(op: TokenStream, span: Span) -> TokenStream {
+    call(
+        "::spvc_shader::dsl::at",
+        vec![
+            op,
+            code("file!()", span),
+            code("line!()", span),
+            code("column!()", span),
+        ],
+        span,
+    )
+}
+
+fn value(op: TokenStream, span: Span) -> TokenStream {
+    call("::spvc_shader::dsl::value", vec![op], span)
+}
+
+/// Generate a float literal.
+fn float(expr: &Expr) -> Result<TokenStream> {
+    match *expr {
+        Expr::Lit(ref lit, negative) => {
+            let span = lit.span();
+            let mut out = TokenStream::new();
+
+            if negative {
+                out.extend(Some(punct('-', span)));
+            }
+
+            out.extend(Some(TokenTree::Literal(lit.clone())));
+            out.extend(code("as f32", span));
+
+            let mut group = Group::new(Delimiter::Parenthesis, out);
+            group.set_span(span);
+            Ok(TokenStream::from(TokenTree::Group(group)))
+        }
+        _ => Err(Error::new(span_of(expr), "expected a literal")),
+    }
+}
+
+fn span_of(expr: &Expr) -> Span {
+    match *expr {
+        Expr::Var(ref ident) => ident.span(),
+        Expr::Lit(ref lit, _) => lit.span(),
+        Expr::Member(_, ref ident) => ident.span(),
+        Expr::Call(ref ident, _) => ident.span(),
+        Expr::Binary(_, span, _, _) => span,
+        Expr::Neg(span, _) => span,
+    }
+}
+
+fn gen_expr(expr: &Expr) -> Result<TokenStream> {
+    let out = match *expr {
+        Expr::Var(ref ident) => {
+            let mut out = TokenStream::from(TokenTree::Ident(ident.clone()));
+            out.extend(code(".clone()", ident.span()));
+            out
+        }
+        Expr::Lit(ref lit, _) => {
+            call(
+                "::spvc_shader::constant_f32",
+                vec![float(expr)?],
+                lit.span(),
+            )
+        }
+        Expr::Member(ref base, ref member) => {
+            let span = member.span();
+            let name = TokenStream::from(TokenTree::Literal(Literal::string(&member.to_string())));
+            at(
+                call("::spvc_shader::dsl::member", vec![gen_expr(base)?, name], span),
+                span,
+            )
+        }
+        Expr::Call(ref name, ref args) => {
+            let span = name.span();
+
+            let (function, literals) = match (name.to_string().as_str(), args.len()) {
+                ("transpose", 1) => ("::spvc_shader::transpose", 0),
+                ("vec3", 2) => ("::spvc_shader::vec2_to_vec3", 1),
+                ("vec4", 2) => ("::spvc_shader::vec3_to_vec4", 1),
+                ("vec4", 3) => ("::spvc_shader::vec2_to_vec4", 2),
+                (name, count) => {
+                    return Err(Error::new(
+                        span,
+                        format!("unsupported function `{}` with {} arguments", name, count),
+                    ))
+                }
+            };
+
+            let split = args.len() - literals;
+            let mut out = Vec::new();
+
+            for arg in &args[..split] {
+                out.push(value(gen_expr(arg)?, span_of(arg)));
+            }
+
+            for arg in &args[split..] {
+                out.push(float(arg)?);
+            }
+
+            at(call(function, out, span), span)
+        }
+        Expr::Binary(op, span, ref lhs, ref rhs) => {
+            let function = match op {
+                '*' => "::spvc_shader::mul",
+                '+' => "::spvc_shader::add",
+                '-' => "::spvc_shader::sub",
+                _ => return Err(Error::new(span, "unsupported operator")),
+            };
+
+            let lhs = value(gen_expr(lhs)?, span_of(lhs));
+            let rhs = value(gen_expr(rhs)?, span_of(rhs));
+            at(call(function, vec![lhs, rhs], span), span)
+        }
+        Expr::Neg(span, ref value_expr) => {
+            let value_expr = value(gen_expr(value_expr)?, span_of(value_expr));
+            at(call("::spvc_shader::neg", vec![value_expr], span), span)
+        }
+    };
+
+    Ok(out)
+}
+
+fn gen_stmt(stmt: &Stmt, builder: &Ident) -> Result<TokenStream> {
+    let mut out = TokenStream::new();
+
+    match *stmt {
+        Stmt::Let(ref name, ref expr) => {
+            out.extend(code("let", name.span()));
+            out.extend(Some(TokenTree::Ident(name.clone())));
+            out.extend(Some(punct('=', name.span())));
+            out.extend(gen_expr(expr)?);
+            out.extend(Some(punct(';', name.span())));
+        }
+        Stmt::Assign(ref place, span, ref expr) => {
+            let store = call(
+                "::spvc_shader::store",
+                vec![gen_expr(place)?, value(gen_expr(expr)?, span_of(expr))],
+                span,
+            );
+
+            out.extend(Some(TokenTree::Ident(builder.clone())));
+            out.extend(call(".op", vec![at(store, span)], span));
+            out.extend(Some(punct(';', span)));
+        }
+    }
+
+    Ok(out)
+}
+
+fn gen_function(name: &Ident, stmts: &[Stmt]) -> Result<TokenStream> {
+    let builder = Ident::new("__spvc_builder", Span::call_site());
+
+    let mut body = TokenStream::new();
+    body.extend(code("let mut", Span::call_site()));
+    body.extend(Some(TokenTree::Ident(builder.clone())));
+    body.extend(Some(punct('=', Span::call_site())));
+    body.extend(call(
+        "::spvc_shader::FunctionBuilder::new",
+        vec![
+            TokenStream::from(TokenTree::Literal(Literal::string(&name.to_string()))),
+        ],
+        name.span(),
+    ));
+    body.extend(Some(punct(';', Span::call_site())));
+
+    for stmt in stmts {
+        body.extend(gen_stmt(stmt, &builder)?);
+    }
+
+    body.extend(Some(TokenTree::Ident(builder)));
+
+    Ok(TokenStream::from(TokenTree::Group(
+        Group::new(Delimiter::Brace, body),
+    )))
+}
+
+/// Build a function from a GLSL-like body.
+///
+/// Evaluates to a `spvc_shader::FunctionBuilder`.
+#[proc_macro]
+pub fn shader(input: TokenStream) -> TokenStream {
+    let mut parser = Parser::new(input, Span::call_site());
+
+    let result = parser.function().and_then(
+        |(name, stmts)| gen_function(&name, &stmts),
+    );
+
+    match result {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let tokens = e.into_tokens();
+            TokenStream::from_iter(vec![
+                TokenTree::Group(Group::new(Delimiter::Brace, tokens)),
+            ])
+        }
+    }
+}
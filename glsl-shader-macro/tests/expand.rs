@@ -0,0 +1,53 @@
+extern crate glsl_shader_macro;
+extern crate spvc_shader;
+
+use glsl_shader_macro::shader;
+use spvc_shader::errors::*;
+use spvc_shader::*;
+
+#[test]
+fn test_expand_vertex_shader() {
+    let position = InputVar::new("position", vec3(), 0);
+    let tex_coord = InputVar::new("tex_coord", vec2(), 1);
+    let v_tex_coord = OutputVar::new("v_tex_coord", vec2(), 0);
+
+    let gl_position = BuiltInVar::new(
+        "gl_Position",
+        vec4(),
+        StorageClass::Output,
+        BuiltIn::Position,
+    );
+
+    let main = shader! {
+        fn main() {
+            gl_position = vec4(position, 1.0);
+            v_tex_coord = tex_coord;
+        }
+    };
+
+    main.validate().unwrap();
+
+    let mut shader = Shader::new();
+    shader.entry_point_auto(ShaderKind::Vertex, main.returns_void()).unwrap();
+}
+
+#[test]
+fn test_bad_member_points_at_macro() {
+    let position = InputVar::new("position", vec3(), 0);
+    let v_normal = OutputVar::new("v_normal", vec3(), 0);
+
+    let main = shader! {
+        fn main() {
+            v_normal = position.missing;
+        }
+    };
+
+    let diagnostics = match main.validate() {
+        Err(Error(ErrorKind::BadOps(diagnostics), _)) => diagnostics,
+        other => panic!("expected bad operations, got {:?}", other),
+    };
+
+    let cause = diagnostics[0].root_cause();
+    assert_eq!(cause.op, "member");
+    assert_eq!(cause.location.map(|l| l.file), Some(file!()));
+}
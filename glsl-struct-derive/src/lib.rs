@@ -74,8 +74,8 @@ fn impl_glsl_member(field: &Field) -> quote::Tokens {
     toks.append(quote!(
         fn #ident() -> spvc_shader::StructMember  {
             spvc_shader::StructMember {
-                name: stringify!(#ident),
-                ty: ::std::rc::Rc::new(#type_builder),
+                name: ::std::borrow::Cow::Borrowed(stringify!(#ident)),
+                ty: ::std::sync::Arc::new(#type_builder),
                 index: #index,
             }
         }
@@ -95,13 +95,13 @@ fn impl_glsl_struct_fn(name: &syn::Ident, fields: &[Field]) -> quote::Tokens {
     for field in fields {
         let ident = &field.ident;
         toks.append(
-            quote! { members.push(::std::rc::Rc::new(#name::#ident())); },
+            quote! { members.push(::std::sync::Arc::new(#name::#ident())); },
         );
     }
 
     toks.append(quote! {
         spvc_shader::Struct {
-            name: stringify!(#name),
+            name: ::std::borrow::Cow::Borrowed(stringify!(#name)),
             members: members,
         }
     });
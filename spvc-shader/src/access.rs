@@ -1,4 +1,6 @@
 use super::errors::*;
+use super::located::located;
+use super::node::Node;
 use super::op::Op;
 use super::ops::BadOp;
 use super::pointer::Pointer;
@@ -7,57 +9,62 @@ use super::shader::Shader;
 use super::spirv::{StorageClass, Word};
 use super::spirv_type::SpirvType;
 use super::struct_member::StructMember;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Trait for accessing members on operations.
 /// This is typically done with structs.
 pub trait AccessTrait {
     /// Access the given struct member.
-    fn access_member(&self, member: StructMember) -> Rc<Op>;
+    fn access_member(&self, member: StructMember) -> Arc<Op>;
 }
 
-impl<T: 'static> AccessTrait for Rc<T>
+impl<T: 'static> AccessTrait for Arc<T>
 where
     T: Op,
 {
-    fn access_member(&self, member: StructMember) -> Rc<Op> {
-        let base = self.base().map(Clone::clone).unwrap_or_else(
-            || self.clone(),
-        );
-
-        if let Some(storage_class) = self.storage_class() {
-            let mut access_chain = self.access_chain()
-                .map(|slice| slice.to_vec())
-                .unwrap_or_else(|| vec![]);
-
-            access_chain.push(member.index);
-
-            let member_type = member.ty.clone();
-
-            return Rc::new(Access {
-                base: base,
-                storage_class: storage_class,
-                pointer_type: Pointer::new(storage_class, member_type.clone()),
-                accessed_type: member_type.clone(),
-                access_chain: access_chain,
-            });
-        }
-
-        Rc::new(BadOp::new(
-            "access_member",
-            "expected pointer type",
-            vec![self.clone()],
-        ))
+    #[track_caller]
+    fn access_member(&self, member: StructMember) -> Arc<Op> {
+        access(self.clone(), member)
     }
 }
 
+/// Access the given struct member of an operation.
+#[track_caller]
+pub(crate) fn access(op: Arc<Op>, member: StructMember) -> Arc<Op> {
+    let base = op.base().map(Clone::clone).unwrap_or_else(|| op.clone());
+
+    if let Some(storage_class) = op.storage_class() {
+        let mut access_chain = op.access_chain()
+            .map(|slice| slice.to_vec())
+            .unwrap_or_else(|| vec![]);
+
+        access_chain.push(member.index);
+
+        let member_type = member.ty.clone();
+
+        return located(Access {
+            base: base,
+            storage_class: storage_class,
+            pointer_type: Pointer::new(storage_class, member_type.clone()),
+            accessed_type: member_type.clone(),
+            access_chain: access_chain,
+        });
+    }
+
+    located(BadOp::new(
+        "access_member",
+        "expected pointer type",
+        vec![op],
+    ))
+}
+
 /// Accessing fields on structs.
 #[derive(Debug)]
 pub struct Access {
-    pub base: Rc<Op>,
+    pub base: Arc<Op>,
     pub storage_class: StorageClass,
     pub pointer_type: Pointer,
-    pub accessed_type: Rc<SpirvType>,
+    pub accessed_type: Arc<SpirvType>,
     pub access_chain: Vec<u32>,
 }
 
@@ -85,7 +92,7 @@ impl RegOp for RegisteredAccess {
 }
 
 impl Op for Access {
-    fn base(&self) -> Option<&Rc<Op>> {
+    fn base(&self) -> Option<&Arc<Op>> {
         Some(&self.base)
     }
 
@@ -101,6 +108,10 @@ impl Op for Access {
         &self.pointer_type
     }
 
+    fn node(&self) -> Node {
+        Node::Access(&self.base, &self.access_chain)
+    }
+
     fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
         let base = self.base.register_op(shader)?;
 
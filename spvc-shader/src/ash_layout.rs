@@ -0,0 +1,156 @@
+//! # Vulkan layouts using ash
+//!
+//! Converts the [reflection](../reflect/index.html) of an entry point into the plain structs used
+//! by ash to describe descriptor set layouts, push constant ranges and vertex input. No device is
+//! needed to build them.
+
+use super::ash::vk;
+use super::errors::*;
+use super::reflect::{DescriptorKind, EntryPointInfo, TypeInfo};
+use super::shader::ShaderKind;
+use std::ptr;
+
+/// Shader stage flags of the given kind of shader.
+pub fn shader_stage(kind: ShaderKind) -> vk::ShaderStageFlags {
+    match kind {
+        ShaderKind::Vertex => vk::SHADER_STAGE_VERTEX_BIT,
+        ShaderKind::Fragment => vk::SHADER_STAGE_FRAGMENT_BIT,
+    }
+}
+
+/// Layout bindings of every descriptor set used by the entry point, ordered by set.
+pub fn descriptor_set_layout_bindings(
+    info: &EntryPointInfo,
+) -> Vec<(u32, Vec<vk::DescriptorSetLayoutBinding>)> {
+    let stage = shader_stage(info.kind);
+    let mut out = Vec::new();
+
+    for set in &info.descriptor_sets {
+        let bindings = set.bindings
+            .iter()
+            .map(|b| {
+                vk::DescriptorSetLayoutBinding {
+                    binding: b.binding,
+                    descriptor_type: match b.kind {
+                        DescriptorKind::UniformBuffer => vk::DescriptorType::UniformBuffer,
+                        DescriptorKind::StorageBuffer => vk::DescriptorType::StorageBuffer,
+                        DescriptorKind::Sampler => vk::DescriptorType::Sampler,
+                        DescriptorKind::CombinedImageSampler => {
+                            vk::DescriptorType::CombinedImageSampler
+                        }
+                        DescriptorKind::SampledImage => vk::DescriptorType::SampledImage,
+                        DescriptorKind::StorageImage => vk::DescriptorType::StorageImage,
+                    },
+                    descriptor_count: 1,
+                    stage_flags: stage,
+                    p_immutable_samplers: ptr::null(),
+                }
+            })
+            .collect();
+
+        out.push((set.set, bindings));
+    }
+
+    out
+}
+
+/// Push constant ranges used by the entry point.
+pub fn push_constant_ranges(info: &EntryPointInfo) -> Vec<vk::PushConstantRange> {
+    let stage = shader_stage(info.kind);
+
+    info.push_constants
+        .iter()
+        .map(|p| {
+            vk::PushConstantRange {
+                stage_flags: stage,
+                offset: p.offset,
+                size: p.size,
+            }
+        })
+        .collect()
+}
+
+/// Vertex input attributes of the entry point, tightly packed in a single binding.
+///
+/// Matrices are split into one attribute per column.
+pub fn vertex_input_attributes(
+    info: &EntryPointInfo,
+    binding: u32,
+) -> Result<Vec<vk::VertexInputAttributeDescription>> {
+    let mut out = Vec::new();
+    let mut offset = 0;
+
+    for var in &info.inputs {
+        let (format, size, columns) = attribute_format(&var.ty).ok_or(
+            ErrorKind::IllegalInterfaceType,
+        )?;
+
+        for column in 0..columns {
+            out.push(vk::VertexInputAttributeDescription {
+                location: var.location + column,
+                binding: binding,
+                format: format,
+                offset: offset,
+            });
+
+            offset += size;
+        }
+    }
+
+    Ok(out)
+}
+
+/// Vertex input binding matching the attributes from `vertex_input_attributes`.
+pub fn vertex_input_binding(
+    info: &EntryPointInfo,
+    binding: u32,
+) -> Result<vk::VertexInputBindingDescription> {
+    let mut stride = 0;
+
+    for var in &info.inputs {
+        let (_, size, columns) = attribute_format(&var.ty).ok_or(
+            ErrorKind::IllegalInterfaceType,
+        )?;
+
+        stride += size * columns;
+    }
+
+    Ok(vk::VertexInputBindingDescription {
+        binding: binding,
+        stride: stride,
+        input_rate: vk::VertexInputRate::Vertex,
+    })
+}
+
+/// Format, size in bytes and number of locations of a vertex attribute with the given type.
+fn attribute_format(ty: &TypeInfo) -> Option<(vk::Format, u32, u32)> {
+    use self::vk::Format::*;
+
+    let format = match *ty {
+        TypeInfo::Float => (R32Sfloat, 4),
+        TypeInfo::UnsignedInteger => (R32Uint, 4),
+        TypeInfo::Vector {
+            ref component,
+            count,
+        } => {
+            match (component.as_ref(), count) {
+                (&TypeInfo::Float, 2) => (R32g32Sfloat, 8),
+                (&TypeInfo::Float, 3) => (R32g32b32Sfloat, 12),
+                (&TypeInfo::Float, 4) => (R32g32b32a32Sfloat, 16),
+                (&TypeInfo::UnsignedInteger, 2) => (R32g32Uint, 8),
+                (&TypeInfo::UnsignedInteger, 3) => (R32g32b32Uint, 12),
+                (&TypeInfo::UnsignedInteger, 4) => (R32g32b32a32Uint, 16),
+                _ => return None,
+            }
+        }
+        TypeInfo::Matrix {
+            ref column,
+            columns,
+        } => {
+            return attribute_format(column).map(|(format, size, _)| (format, size, columns));
+        }
+        _ => return None,
+    };
+
+    Some((format.0, format.1, 1))
+}
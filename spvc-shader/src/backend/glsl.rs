@@ -0,0 +1,282 @@
+//! GLSL 450 backend.
+
+use super::{entry_point, structs, uniforms};
+use decorations::{Interpolation, Sampling, VarDecorations};
+use errors::*;
+use interface::Interface;
+use node::Node;
+use op::Op;
+use shader::Shader;
+use spirv::BuiltIn;
+use spirv_type::SpirvType;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const BACKEND: &'static str = "glsl";
+
+/// Print the entry point with the given name as GLSL 450 source.
+pub fn emit(shader: &Shader, name: &str) -> Result<String> {
+    let entry = entry_point(shader, name)?;
+
+    let mut out = String::new();
+    out.push_str("#version 450\n\n");
+
+    for op in &entry.interface {
+        match op.node() {
+            Node::Input(var) => {
+                let location = shader.locations.resolve(Interface::Input(var))?;
+
+                out.push_str(&layout(location, &var.decorations));
+                out.push_str(&qualifiers(&var.decorations));
+                out.push_str("in ");
+                out.push_str(&type_name(var.pointer.pointee_type.as_ref())?);
+                writeln!(out, " {};", var.name).unwrap();
+            }
+            Node::Output(var) => {
+                let location = shader.locations.resolve(Interface::Output(var))?;
+
+                out.push_str(&layout(location, &var.decorations));
+                out.push_str(&qualifiers(&var.decorations));
+                out.push_str("out ");
+                out.push_str(&type_name(var.pointer.pointee_type.as_ref())?);
+                writeln!(out, " {};", var.name).unwrap();
+            }
+            // built-ins are implicitly declared.
+            Node::BuiltIn(_) => {}
+            _ => {
+                return Err(
+                    ErrorKind::UnsupportedByBackend(BACKEND, format!("interface {:?}", op)).into(),
+                );
+            }
+        }
+    }
+
+    let uniforms = uniforms(entry.function.ops());
+
+    for st in structs(BACKEND, &uniforms)? {
+        writeln!(out, "\nstruct {} {{", st.name).unwrap();
+
+        for m in &st.members {
+            writeln!(out, "    {} {};", type_name(m.ty.as_ref())?, m.name).unwrap();
+        }
+
+        out.push_str("};\n");
+    }
+
+    for var in uniforms {
+        let st = var.pointer.pointee_type.as_struct().ok_or_else(|| {
+            ErrorKind::UnsupportedByBackend(
+                BACKEND,
+                format!("uniform `{}` which is not a struct", var.name),
+            )
+        })?;
+
+        // the block has no instance name, so the variable is accessed as a global.
+        writeln!(
+            out,
+            "\nlayout(set = {}, binding = {}) {} {}_block {{\n    {} {};\n}};",
+            var.set,
+            var.binding,
+            if var.is_storage_buffer() { "buffer" } else { "uniform" },
+            var.name,
+            st.name,
+            var.name
+        ).unwrap();
+    }
+
+    writeln!(out, "\nvoid {}() {{", entry.name).unwrap();
+
+    for op in entry.function.ops() {
+        match op.node() {
+            Node::Store(dest, source) => {
+                writeln!(out, "    {} = {};", expr(dest)?, expr(source)?).unwrap();
+            }
+            _ => {
+                writeln!(out, "    {};", expr(op)?).unwrap();
+            }
+        }
+    }
+
+    out.push_str("}\n");
+    Ok(out)
+}
+
+/// GLSL name of the given type.
+pub fn type_name(ty: &SpirvType) -> Result<String> {
+    if ty.as_float().is_some() {
+        return Ok(String::from("float"));
+    }
+
+    if ty.as_unsigned_integer().is_some() {
+        return Ok(String::from("uint"));
+    }
+
+    if ty.as_bool().is_some() {
+        return Ok(String::from("bool"));
+    }
+
+    if let Some(vector) = ty.as_vector() {
+        let prefix = if vector.component.as_float().is_some() {
+            ""
+        } else if vector.component.as_unsigned_integer().is_some() {
+            "u"
+        } else if vector.component.as_bool().is_some() {
+            "b"
+        } else {
+            return unsupported_type(ty);
+        };
+
+        return Ok(format!("{}vec{}", prefix, vector.component_count));
+    }
+
+    if let Some(matrix) = ty.as_matrix() {
+        if let Some(column) = matrix.column_type.as_vector() {
+            if column.component.as_float().is_some() {
+                if column.component_count == matrix.column_count {
+                    return Ok(format!("mat{}", matrix.column_count));
+                }
+
+                return Ok(format!(
+                    "mat{}x{}",
+                    matrix.column_count,
+                    column.component_count
+                ));
+            }
+        }
+    }
+
+    if let Some(st) = ty.as_struct() {
+        return Ok(st.name.to_string());
+    }
+
+    unsupported_type(ty)
+}
+
+fn unsupported_type<T>(ty: &SpirvType) -> Result<T> {
+    Err(ErrorKind::UnsupportedByBackend(BACKEND, format!("type `{}`", ty.display())).into())
+}
+
+/// Print a float constant. GLSL has no literals for infinity or NaN.
+fn float(value: f32) -> Result<String> {
+    if !value.is_finite() {
+        return Err(
+            ErrorKind::UnsupportedByBackend(BACKEND, format!("float constant `{}`", value)).into(),
+        );
+    }
+
+    Ok(format!("{:?}", value))
+}
+
+/// `layout(...)` qualifier of an input or output variable.
+fn layout(location: u32, decorations: &VarDecorations) -> String {
+    let mut out = format!("layout(location = {}", location);
+
+    if let Some(component) = decorations.component {
+        write!(out, ", component = {}", component).unwrap();
+    }
+
+    if let Some(index) = decorations.index {
+        write!(out, ", index = {}", index).unwrap();
+    }
+
+    out.push_str(") ");
+    out
+}
+
+/// Invariance, interpolation and sampling qualifiers of an input or output variable.
+fn qualifiers(decorations: &VarDecorations) -> String {
+    let mut out = String::new();
+
+    if decorations.invariant {
+        out.push_str("invariant ");
+    }
+
+    match decorations.interpolation {
+        Interpolation::Smooth => {}
+        Interpolation::Flat => out.push_str("flat "),
+        Interpolation::NoPerspective => out.push_str("noperspective "),
+    }
+
+    match decorations.sampling {
+        Sampling::Center => {}
+        Sampling::Centroid => out.push_str("centroid "),
+        Sampling::Sample => out.push_str("sample "),
+    }
+
+    out
+}
+
+/// Print an operation as a GLSL expression.
+fn expr(op: &Arc<Op>) -> Result<String> {
+    let out = match op.node() {
+        Node::Input(var) => var.name.clone(),
+        Node::Output(var) => var.name.clone(),
+        Node::Uniform(var) => var.name.clone(),
+        Node::BuiltIn(var) => {
+            match var.built_in() {
+                BuiltIn::Position => String::from("gl_Position"),
+                BuiltIn::PointSize => String::from("gl_PointSize"),
+                BuiltIn::FragCoord => String::from("gl_FragCoord"),
+                BuiltIn::FragDepth => String::from("gl_FragDepth"),
+                BuiltIn::VertexIndex => String::from("gl_VertexIndex"),
+                BuiltIn::InstanceIndex => String::from("gl_InstanceIndex"),
+                other => {
+                    return Err(
+                        ErrorKind::UnsupportedByBackend(BACKEND, format!("built-in {:?}", other))
+                            .into(),
+                    );
+                }
+            }
+        }
+        Node::Access(base, access_chain) => {
+            let mut out = expr(base)?;
+            let mut ty = base.op_type().as_pointer().map(|p| p.pointee_type);
+
+            for index in access_chain {
+                let member = ty.as_ref()
+                    .and_then(|ty| ty.as_struct())
+                    .and_then(|st| st.members.get(*index as usize).cloned())
+                    .ok_or_else(|| {
+                        ErrorKind::UnsupportedByBackend(BACKEND, format!("access {:?}", op))
+                    })?;
+
+                out.push('.');
+                out.push_str(&member.name);
+                ty = Some(member.ty.clone());
+            }
+
+            out
+        }
+        Node::Load(object) => expr(object)?,
+        Node::Store(dest, source) => format!("{} = {}", expr(dest)?, expr(source)?),
+        Node::Add(lhs, rhs) => format!("({} + {})", expr(lhs)?, expr(rhs)?),
+        Node::Sub(lhs, rhs) => format!("({} - {})", expr(lhs)?, expr(rhs)?),
+        Node::Mul(lhs, rhs) => format!("({} * {})", expr(lhs)?, expr(rhs)?),
+        Node::Neg(value) => format!("(-{})", expr(value)?),
+        Node::Extract(composite, index) => {
+            if composite.op_type().as_vector().is_some() && index < 4 {
+                format!("{}.{}", expr(composite)?, &"xyzw"[index as usize..index as usize + 1])
+            } else {
+                format!("{}[{}]", expr(composite)?, index)
+            }
+        }
+        Node::ConstantF32(value) => float(value)?,
+        Node::Expand(source, constants) => {
+            let result_type = type_name(op.op_type())?;
+            let mut out = format!("{}({}", result_type, expr(source)?);
+
+            for c in constants {
+                write!(out, ", {}", float(c)?).unwrap();
+            }
+
+            out.push(')');
+            out
+        }
+        Node::Transpose(matrix) => format!("transpose({})", expr(matrix)?),
+        Node::Inverse(matrix) => format!("inverse({})", expr(matrix)?),
+        Node::Mat4ToMat3(matrix) => format!("mat3({})", expr(matrix)?),
+        Node::Bad(bad_op) => return Err(bad_op.error_at(op.location())),
+    };
+
+    Ok(out)
+}
@@ -0,0 +1,108 @@
+//! # Backends other than SPIR-V
+//!
+//! Backends walk the same operations that are registered when a shader is built, and print them
+//! as source in other shading languages.
+
+pub mod glsl;
+pub mod wgsl;
+
+use errors::*;
+use node::Node;
+use op::Op;
+use shader::{EntryPoint, Shader};
+use spirv_type::{SpirvType, TypeKey};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use types::Struct;
+use uniform_var::UniformVar;
+
+/// Find the entry point with the given name.
+fn entry_point<'a>(shader: &'a Shader, name: &str) -> Result<&'a EntryPoint> {
+    shader
+        .entry_points
+        .iter()
+        .find(|e| e.name == name)
+        .ok_or_else(|| ErrorKind::MissingEntryPoint(name.to_string()).into())
+}
+
+/// Walk all operations reachable from the given roots depth-first, calling `visitor` once for
+/// each.
+pub(crate) fn walk<'a, F>(roots: &'a [Arc<Op>], mut visitor: F)
+where
+    F: FnMut(&'a Arc<Op>),
+{
+    let mut seen = HashSet::new();
+    let mut queue: Vec<&'a Arc<Op>> = roots.iter().rev().collect();
+
+    while let Some(op) = queue.pop() {
+        if !seen.insert(&**op as *const Op as *const ()) {
+            continue;
+        }
+
+        visitor(op);
+        queue.extend(op.node().children().into_iter().rev());
+    }
+}
+
+/// Collect the uniform variables used by the given operations, in order of first use.
+fn uniforms<'a>(ops: &'a [Arc<Op>]) -> Vec<&'a UniformVar> {
+    let mut out: Vec<&'a UniformVar> = Vec::new();
+
+    walk(ops, |op| if let Node::Uniform(var) = op.node() {
+        if !out.iter().any(|u| u.name == var.name) {
+            out.push(var);
+        }
+    });
+
+    out
+}
+
+/// Collect the structs used by the given uniform variables, each once and after the structs of
+/// its members.
+///
+/// Struct names are global in other shading languages, so distinct structs with the same name
+/// are an error.
+fn structs(backend: &'static str, vars: &[&UniformVar]) -> Result<Vec<Struct>> {
+    let mut keys = HashMap::new();
+    let mut out = Vec::new();
+
+    for var in vars {
+        if let Some(st) = var.pointer.pointee_type.as_struct() {
+            add_struct(backend, st, &mut keys, &mut out)?;
+        }
+    }
+
+    Ok(out)
+}
+
+fn add_struct(
+    backend: &'static str,
+    st: Struct,
+    keys: &mut HashMap<String, TypeKey>,
+    out: &mut Vec<Struct>,
+) -> Result<()> {
+    let key = st.key();
+
+    match keys.get(st.name.as_ref()) {
+        Some(existing) if *existing == key => return Ok(()),
+        Some(_) => {
+            return Err(
+                ErrorKind::UnsupportedByBackend(
+                    backend,
+                    format!("distinct structs named `{}`", st.name),
+                ).into(),
+            );
+        }
+        None => {}
+    }
+
+    for m in &st.members {
+        if let Some(member) = m.ty.as_struct() {
+            add_struct(backend, member, keys, out)?;
+        }
+    }
+
+    keys.insert(st.name.to_string(), key);
+    out.push(st);
+    Ok(())
+}
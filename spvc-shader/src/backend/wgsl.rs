@@ -0,0 +1,344 @@
+//! WGSL backend.
+//!
+//! Inputs and outputs of the entry point are gathered into the structs `Input` and `Output`, which
+//! are passed to and returned from the entry point function.
+
+use super::{entry_point, structs, uniforms};
+use decorations::{Interpolation, Sampling, VarDecorations};
+use errors::*;
+use interface::Interface;
+use node::Node;
+use op::Op;
+use shader::{Shader, ShaderKind};
+use spirv::{BuiltIn, StorageClass};
+use spirv_type::SpirvType;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const BACKEND: &'static str = "wgsl";
+
+fn unsupported<T>(what: String) -> Result<T> {
+    Err(ErrorKind::UnsupportedByBackend(BACKEND, what).into())
+}
+
+/// Print the entry point with the given name as WGSL source.
+pub fn emit(shader: &Shader, name: &str) -> Result<String> {
+    let entry = entry_point(shader, name)?;
+
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+
+    for op in &entry.interface {
+        match op.node() {
+            Node::Input(var) => {
+                let location = shader.locations.resolve(Interface::Input(var))?;
+
+                let attributes = location_attributes(&var.name, location, &var.decorations)?;
+                let ty = type_name(var.pointer.pointee_type.as_ref())?;
+                inputs.push(format!("    {} {}: {},", attributes, var.name, ty));
+            }
+            Node::Output(var) => {
+                let location = shader.locations.resolve(Interface::Output(var))?;
+
+                let attributes = location_attributes(&var.name, location, &var.decorations)?;
+                let ty = type_name(var.pointer.pointee_type.as_ref())?;
+                outputs.push(format!("    {} {}: {},", attributes, var.name, ty));
+            }
+            Node::BuiltIn(var) => {
+                let ty = match op.op_type().as_pointer() {
+                    Some(pointer) => type_name(pointer.pointee_type.as_ref())?,
+                    None => return unsupported(format!("built-in {:?}", op)),
+                };
+
+                let field = format!(
+                    "    @builtin({}) {}: {},",
+                    built_in_name(var.built_in())?,
+                    var.name(),
+                    ty
+                );
+
+                match op.storage_class() {
+                    Some(StorageClass::Input) => inputs.push(field),
+                    _ => outputs.push(field),
+                }
+            }
+            _ => return unsupported(format!("interface {:?}", op)),
+        }
+    }
+
+    let mut out = String::new();
+
+    let uniforms = uniforms(entry.function.ops());
+
+    for st in structs(BACKEND, &uniforms)? {
+        writeln!(out, "struct {} {{", st.name).unwrap();
+
+        for m in &st.members {
+            // booleans have no defined layout in host-shareable buffers.
+            if has_bool(m.ty.as_ref()) {
+                return unsupported(format!("bool member `{}` of uniform `{}`", m.name, st.name));
+            }
+
+            writeln!(out, "    {}: {},", m.name, type_name(m.ty.as_ref())?).unwrap();
+        }
+
+        writeln!(out, "}}\n").unwrap();
+    }
+
+    for var in uniforms {
+        let st = match var.pointer.pointee_type.as_struct() {
+            Some(st) => st,
+            None => return unsupported(format!("uniform `{}` which is not a struct", var.name)),
+        };
+
+        writeln!(
+            out,
+            "@group({}) @binding({}) var<{}> {}: {};\n",
+            var.set,
+            var.binding,
+            if var.is_storage_buffer() {
+                "storage, read_write"
+            } else {
+                "uniform"
+            },
+            var.name,
+            st.name
+        ).unwrap();
+    }
+
+    if !inputs.is_empty() {
+        writeln!(out, "struct Input {{\n{}\n}}\n", inputs.join("\n")).unwrap();
+    }
+
+    if !outputs.is_empty() {
+        writeln!(out, "struct Output {{\n{}\n}}\n", outputs.join("\n")).unwrap();
+    }
+
+    let stage = match entry.kind {
+        ShaderKind::Vertex => "vertex",
+        ShaderKind::Fragment => "fragment",
+    };
+
+    write!(out, "@{}\nfn {}(", stage, entry.name).unwrap();
+
+    if !inputs.is_empty() {
+        out.push_str("input: Input");
+    }
+
+    out.push(')');
+
+    if !outputs.is_empty() {
+        out.push_str(" -> Output");
+    }
+
+    out.push_str(" {\n");
+
+    if !outputs.is_empty() {
+        out.push_str("    var output: Output;\n");
+    }
+
+    for op in entry.function.ops() {
+        match op.node() {
+            Node::Store(dest, source) => {
+                writeln!(out, "    {} = {};", expr(dest)?, expr(source)?).unwrap();
+            }
+            _ => {
+                writeln!(out, "    _ = {};", expr(op)?).unwrap();
+            }
+        }
+    }
+
+    if !outputs.is_empty() {
+        out.push_str("    return output;\n");
+    }
+
+    out.push_str("}\n");
+    Ok(out)
+}
+
+/// WGSL name of the given type.
+pub fn type_name(ty: &SpirvType) -> Result<String> {
+    if let Some(name) = scalar_name(ty) {
+        return Ok(String::from(name));
+    }
+
+    if let Some(vector) = ty.as_vector() {
+        if let Some(component) = scalar_name(vector.component.as_ref()) {
+            return Ok(format!("vec{}<{}>", vector.component_count, component));
+        }
+    }
+
+    if let Some(matrix) = ty.as_matrix() {
+        if let Some(column) = matrix.column_type.as_vector() {
+            if column.component.as_float().is_some() {
+                return Ok(format!(
+                    "mat{}x{}<f32>",
+                    matrix.column_count,
+                    column.component_count
+                ));
+            }
+        }
+    }
+
+    if let Some(st) = ty.as_struct() {
+        return Ok(st.name.to_string());
+    }
+
+    unsupported(format!("type `{}`", ty.display()))
+}
+
+fn scalar_name(ty: &SpirvType) -> Option<&'static str> {
+    if ty.as_float().is_some() {
+        return Some("f32");
+    }
+
+    if ty.as_unsigned_integer().is_some() {
+        return Some("u32");
+    }
+
+    if ty.as_bool().is_some() {
+        return Some("bool");
+    }
+
+    None
+}
+
+/// Check if the given type is a boolean, or a vector of booleans.
+fn has_bool(ty: &SpirvType) -> bool {
+    match ty.as_vector() {
+        Some(vector) => vector.component.as_bool().is_some(),
+        None => ty.as_bool().is_some(),
+    }
+}
+
+/// Print a float constant. WGSL has no literals for infinity or NaN.
+fn float(value: f32) -> Result<String> {
+    if !value.is_finite() {
+        return unsupported(format!("float constant `{}`", value));
+    }
+
+    Ok(format!("{:?}", value))
+}
+
+/// Name of a built-in, as used in `@builtin(...)`.
+fn built_in_name(built_in: BuiltIn) -> Result<&'static str> {
+    let name = match built_in {
+        BuiltIn::Position | BuiltIn::FragCoord => "position",
+        BuiltIn::FragDepth => "frag_depth",
+        BuiltIn::VertexIndex => "vertex_index",
+        BuiltIn::InstanceIndex => "instance_index",
+        BuiltIn::FrontFacing => "front_facing",
+        other => return unsupported(format!("built-in {:?}", other)),
+    };
+
+    Ok(name)
+}
+
+/// Attributes of an input or output variable.
+fn location_attributes(name: &str, location: u32, decorations: &VarDecorations) -> Result<String> {
+    if decorations.component.is_some() {
+        return unsupported(format!("component decoration on `{}`", name));
+    }
+
+    if decorations.index.is_some() {
+        return unsupported(format!("index decoration on `{}`", name));
+    }
+
+    if decorations.invariant {
+        return unsupported(format!("invariant variable `{}`", name));
+    }
+
+    let mut out = format!("@location({})", location);
+
+    let interpolation = match decorations.interpolation {
+        Interpolation::Smooth => "perspective",
+        Interpolation::Flat => "flat",
+        Interpolation::NoPerspective => "linear",
+    };
+
+    match (decorations.interpolation, decorations.sampling) {
+        (Interpolation::Smooth, Sampling::Center) => {}
+        (Interpolation::Flat, Sampling::Center) => out.push_str(" @interpolate(flat)"),
+        (Interpolation::Flat, _) => {
+            return unsupported(format!("sampling of flat variable `{}`", name));
+        }
+        (_, Sampling::Center) => write!(out, " @interpolate({})", interpolation).unwrap(),
+        (_, Sampling::Centroid) => {
+            write!(out, " @interpolate({}, centroid)", interpolation).unwrap()
+        }
+        (_, Sampling::Sample) => write!(out, " @interpolate({}, sample)", interpolation).unwrap(),
+    }
+
+    Ok(out)
+}
+
+/// Print an operation as a WGSL expression.
+fn expr(op: &Arc<Op>) -> Result<String> {
+    let out = match op.node() {
+        Node::Input(var) => format!("input.{}", var.name),
+        Node::Output(var) => format!("output.{}", var.name),
+        Node::Uniform(var) => var.name.clone(),
+        Node::BuiltIn(var) => {
+            match op.storage_class() {
+                Some(StorageClass::Input) => format!("input.{}", var.name()),
+                _ => format!("output.{}", var.name()),
+            }
+        }
+        Node::Access(base, access_chain) => {
+            let mut out = expr(base)?;
+            let mut ty = base.op_type().as_pointer().map(|p| p.pointee_type);
+
+            for index in access_chain {
+                let member = match ty.as_ref().and_then(|ty| ty.as_struct()).and_then(|st| {
+                    st.members.get(*index as usize).cloned()
+                }) {
+                    Some(member) => member,
+                    None => return unsupported(format!("access {:?}", op)),
+                };
+
+                out.push('.');
+                out.push_str(&member.name);
+                ty = Some(member.ty.clone());
+            }
+
+            out
+        }
+        Node::Load(object) => expr(object)?,
+        Node::Store(_, _) => return unsupported(format!("nested store {:?}", op)),
+        Node::Add(lhs, rhs) => format!("({} + {})", expr(lhs)?, expr(rhs)?),
+        Node::Sub(lhs, rhs) => format!("({} - {})", expr(lhs)?, expr(rhs)?),
+        Node::Mul(lhs, rhs) => format!("({} * {})", expr(lhs)?, expr(rhs)?),
+        Node::Neg(value) => format!("(-{})", expr(value)?),
+        Node::Extract(composite, index) => {
+            if composite.op_type().as_vector().is_some() && index < 4 {
+                format!("{}.{}", expr(composite)?, &"xyzw"[index as usize..index as usize + 1])
+            } else {
+                format!("{}[{}]", expr(composite)?, index)
+            }
+        }
+        Node::ConstantF32(value) => float(value)?,
+        Node::Expand(source, constants) => {
+            let mut out = format!("{}({}", type_name(op.op_type())?, expr(source)?);
+
+            for c in constants {
+                write!(out, ", {}", float(c)?).unwrap();
+            }
+
+            out.push(')');
+            out
+        }
+        Node::Transpose(matrix) => format!("transpose({})", expr(matrix)?),
+        Node::Inverse(_) => return unsupported(String::from("matrix inverse")),
+        Node::Mat4ToMat3(matrix) => {
+            let matrix = expr(matrix)?;
+
+            format!(
+                "mat3x3<f32>({m}[0].xyz, {m}[1].xyz, {m}[2].xyz)",
+                m = matrix
+            )
+        }
+        Node::Bad(bad_op) => return Err(bad_op.error_at(op.location())),
+    };
+
+    Ok(out)
+}
@@ -1,5 +1,6 @@
 use super::errors::*;
 use super::interface::Interface;
+use super::node::Node;
 use super::op::Op;
 use super::op_key::OpKey;
 use super::pointer::Pointer;
@@ -8,7 +9,7 @@ use super::rspirv::mr::Operand;
 use super::shader::Shader;
 use super::spirv::{BuiltIn, Decoration, StorageClass};
 use super::spirv_type::{SpirvType, WrapperType};
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Represents built-in variables.
 #[derive(Debug)]
@@ -27,7 +28,7 @@ impl WrapperType for BuiltInVar {
 
 impl Op for BuiltInVar {
     fn as_interface(&self) -> Option<Interface> {
-        return Some(Interface::BuiltIn);
+        return Some(Interface::BuiltIn(self));
     }
 
     fn storage_class(&self) -> Option<StorageClass> {
@@ -38,7 +39,12 @@ impl Op for BuiltInVar {
         &self.ty
     }
 
+    fn node(&self) -> Node {
+        Node::BuiltIn(self)
+    }
+
     fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
+        shader.require_built_in(self.built_in);
         let variable_type = self.ty.register_type(shader)?;
 
         let id = shader.cache_op(
@@ -78,12 +84,22 @@ impl BuiltInVar {
         ty: T,
         storage_class: StorageClass,
         built_in: BuiltIn,
-    ) -> Rc<BuiltInVar> {
-        Rc::new(BuiltInVar {
+    ) -> Arc<BuiltInVar> {
+        Arc::new(BuiltInVar {
             name: String::from(name),
             storage_class: storage_class,
-            ty: Pointer::new(storage_class, Rc::new(ty)),
+            ty: Pointer::new(storage_class, Arc::new(ty)),
             built_in: built_in,
         })
     }
+
+    /// The built-in this variable is bound to.
+    pub fn built_in(&self) -> BuiltIn {
+        self.built_in
+    }
+
+    /// Name of the built-in variable.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
 }
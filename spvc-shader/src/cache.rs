@@ -0,0 +1,72 @@
+//! # Caching of built shaders
+//!
+//! Operations and types are `Send` and `Sync`, so permutations of a shader can be built on
+//! several threads at once. A `Cache` keeps what was built, like modules or reflected entry
+//! points, so that each permutation is only built once.
+
+use super::errors::*;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Thread-safe cache of values built from shaders, by key.
+///
+/// The cache can be used as a `static`:
+///
+/// ```rust,ignore
+/// static MODULES: Cache<Permutation, Module> = Cache::new();
+/// ```
+#[derive(Debug)]
+pub struct Cache<K, V> {
+    entries: Mutex<Option<HashMap<K, Arc<V>>>>,
+}
+
+impl<K, V> Cache<K, V> {
+    /// Create a new, empty cache.
+    pub const fn new() -> Cache<K, V> {
+        Cache { entries: Mutex::new(None) }
+    }
+}
+
+impl<K, V> Default for Cache<K, V> {
+    fn default() -> Cache<K, V> {
+        Cache::new()
+    }
+}
+
+impl<K: Eq + Hash, V> Cache<K, V> {
+    /// Get the value for the given key, if it has been built.
+    pub fn get(&self, key: &K) -> Option<Arc<V>> {
+        self.entries().as_ref().and_then(|e| e.get(key)).cloned()
+    }
+
+    /// Get the value for the given key, or build it and insert it into the cache.
+    ///
+    /// The cache is not locked while building, so values for different keys can be built in
+    /// parallel. If the same key is built by several threads at once, the first value inserted is
+    /// kept and returned to all of them.
+    pub fn get_or_build<F>(&self, key: K, build: F) -> Result<Arc<V>>
+    where
+        F: FnOnce() -> Result<V>,
+    {
+        if let Some(value) = self.get(&key) {
+            return Ok(value);
+        }
+
+        let value = Arc::new(build()?);
+
+        let mut entries = self.entries();
+        let entries = entries.get_or_insert_with(HashMap::new);
+        Ok(entries.entry(key).or_insert(value).clone())
+    }
+
+    /// Remove all values from the cache.
+    pub fn clear(&self) {
+        *self.entries() = None;
+    }
+
+    fn entries(&self) -> MutexGuard<Option<HashMap<K, Arc<V>>>> {
+        // the map is never left in an inconsistent state, so a poisoned lock can be ignored.
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,425 @@
+//! # Structural comparison of SPIR-V modules
+//!
+//! Ids are renumbered in the order they are defined, and debug names are ignored, so that modules
+//! produced by different compilers (like glslang) can be compared for semantic differences.
+//! Functions are identified by the entry points using them, and strings by their contents.
+
+use super::errors::*;
+use super::rspirv;
+use super::rspirv::mr::{Instruction, Module, Operand};
+use super::spirv::{self, Word};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Section of a module in which a difference was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// `OpCapability` and `OpExtension`.
+    Capabilities,
+    /// `OpEntryPoint`.
+    EntryPoints,
+    /// `OpExecutionMode`.
+    ExecutionModes,
+    /// `OpDecorate` and `OpMemberDecorate`.
+    Decorations,
+    /// Types, constants and global variables.
+    Types,
+    /// Instructions in function bodies.
+    Instructions,
+}
+
+/// A difference between two modules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference {
+    /// Present in the expected module, but missing from the actual one.
+    Missing(Section, String),
+    /// Present in the actual module, but not in the expected one.
+    Extra(Section, String),
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Difference::Missing(section, ref what) => write!(fmt, "- {:?}: {}", section, what),
+            Difference::Extra(section, ref what) => write!(fmt, "+ {:?}: {}", section, what),
+        }
+    }
+}
+
+/// Load a module from SPIR-V bytecode.
+pub fn load_bytes(bytes: &[u8]) -> Result<Module> {
+    if bytes.len() % 4 != 0 {
+        return Err(ErrorKind::ModuleParse(String::from("length is not a multiple of 4")).into());
+    }
+
+    let little_endian = |c: &[u8]| {
+        (c[0] as u32) | (c[1] as u32) << 8 | (c[2] as u32) << 16 | (c[3] as u32) << 24
+    };
+
+    let big_endian = |c: &[u8]| {
+        (c[3] as u32) | (c[2] as u32) << 8 | (c[1] as u32) << 16 | (c[0] as u32) << 24
+    };
+
+    // the magic number determines the byte order of the whole module.
+    let words: Vec<u32> = match bytes.get(0..4) {
+        Some(magic) if little_endian(magic) == spirv::MAGIC_NUMBER => {
+            bytes.chunks(4).map(little_endian).collect()
+        }
+        Some(magic) if big_endian(magic) == spirv::MAGIC_NUMBER => {
+            bytes.chunks(4).map(big_endian).collect()
+        }
+        _ => return Err(ErrorKind::ModuleParse(String::from("bad magic number")).into()),
+    };
+
+    let mut loader = rspirv::mr::Loader::new();
+
+    rspirv::binary::parse_words(&words, &mut loader).map_err(|e| {
+        ErrorKind::ModuleParse(format!("{:?}", e))
+    })?;
+
+    Ok(loader.module())
+}
+
+/// Compare the actual module against the expected one.
+pub fn compare(expected: &Module, actual: &Module) -> Vec<Difference> {
+    let expected = Canonical::new(expected);
+    let actual = Canonical::new(actual);
+
+    let mut out = Vec::new();
+
+    for &(section, ref expected) in &expected.sections {
+        let actual = actual
+            .sections
+            .iter()
+            .find(|s| s.0 == section)
+            .map(|s| &s.1[..])
+            .unwrap_or(&[]);
+
+        diff(section, expected, actual, &mut out);
+    }
+
+    out
+}
+
+/// Multiset difference between two lists of canonical instructions.
+fn diff(section: Section, expected: &[String], actual: &[String], out: &mut Vec<Difference>) {
+    let mut counts: HashMap<&str, i32> = HashMap::new();
+
+    for e in expected {
+        *counts.entry(e.as_str()).or_insert(0) += 1;
+    }
+
+    for a in actual {
+        *counts.entry(a.as_str()).or_insert(0) -= 1;
+    }
+
+    for e in expected {
+        if let Some(count) = counts.get_mut(e.as_str()) {
+            if *count > 0 {
+                *count -= 1;
+                out.push(Difference::Missing(section, e.clone()));
+            }
+        }
+    }
+
+    for a in actual {
+        if let Some(count) = counts.get_mut(a.as_str()) {
+            if *count < 0 {
+                *count += 1;
+                out.push(Difference::Extra(section, a.clone()));
+            }
+        }
+    }
+}
+
+/// A module, with every instruction described without ids or names.
+struct Canonical {
+    ids: HashMap<Word, String>,
+    /// Number of ids defined so far.
+    defined: u32,
+    /// Decorations of each id, used to identify variables.
+    decorations: HashMap<Word, Vec<String>>,
+    sections: Vec<(Section, Vec<String>)>,
+}
+
+impl Canonical {
+    fn new(module: &Module) -> Canonical {
+        let mut c = Canonical {
+            ids: HashMap::new(),
+            defined: 0,
+            decorations: HashMap::new(),
+            sections: Vec::new(),
+        };
+
+        for inst in &module.annotations {
+            if inst.class.opcode != spirv::Op::Decorate {
+                continue;
+            }
+
+            if let Some(&Operand::IdRef(id)) = inst.operands.get(0) {
+                let decoration = inst.operands[1..]
+                    .iter()
+                    .map(|o| format!("{:?}", o))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                let decorations = c.decorations.entry(id).or_insert_with(Vec::new);
+                decorations.push(decoration);
+                decorations.sort();
+            }
+        }
+
+        // strings are referenced by `OpLine`.
+        for inst in &module.debugs {
+            match (inst.class.opcode, inst.result_id, inst.operands.get(0)) {
+                (spirv::Op::String, Some(id), Some(&Operand::LiteralString(ref string))) => {
+                    c.ids.insert(id, format!("String({:?})", string));
+                }
+                _ => {}
+            }
+        }
+
+        for inst in &module.ext_inst_imports {
+            c.define(inst);
+        }
+
+        for inst in &module.types_global_values {
+            c.define(inst);
+        }
+
+        // functions are identified by the entry points using them, or their index.
+        for (index, function) in module.functions.iter().enumerate() {
+            if let Some(id) = function.def.as_ref().and_then(|d| d.result_id) {
+                let name = module
+                    .entry_points
+                    .iter()
+                    .find(|e| e.operands.get(1) == Some(&Operand::IdRef(id)))
+                    .and_then(|e| match e.operands.get(2) {
+                        Some(&Operand::LiteralString(ref name)) => Some(name.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or_else(|| index.to_string());
+
+                c.ids.insert(id, format!("Function({})", name));
+            }
+        }
+
+        let mut capabilities = Vec::new();
+
+        for inst in module.capabilities.iter().chain(module.extensions.iter()) {
+            capabilities.push(c.describe(inst));
+        }
+
+        let entry_points = module.entry_points.iter().map(|i| c.describe(i)).collect();
+        let execution_modes = module.execution_modes.iter().map(|i| c.describe(i)).collect();
+        let decorations = module.annotations.iter().map(|i| c.describe(i)).collect();
+
+        let types = module
+            .types_global_values
+            .iter()
+            .map(|i| c.describe(i))
+            .collect();
+
+        let mut instructions = Vec::new();
+
+        for function in &module.functions {
+            for (index, param) in function.parameters.iter().enumerate() {
+                if let Some(id) = param.result_id {
+                    c.ids.insert(id, format!("Param({})", index));
+                }
+            }
+
+            for (index, block) in function.basic_blocks.iter().enumerate() {
+                if let Some(id) = block.label.as_ref().and_then(|l| l.result_id) {
+                    c.ids.insert(id, format!("Label({})", index));
+                }
+            }
+
+            for block in &function.basic_blocks {
+                for inst in &block.instructions {
+                    instructions.push(c.define(inst));
+                }
+            }
+        }
+
+        c.sections.push((Section::Capabilities, capabilities));
+        c.sections.push((Section::EntryPoints, entry_points));
+        c.sections.push((Section::ExecutionModes, execution_modes));
+        c.sections.push((Section::Decorations, decorations));
+        c.sections.push((Section::Types, types));
+        c.sections.push((Section::Instructions, instructions));
+        c
+    }
+
+    /// Describe the instruction, and number its result in the order of definition.
+    fn define(&mut self, inst: &Instruction) -> String {
+        let description = self.describe(inst);
+
+        if let Some(id) = inst.result_id {
+            let number = format!("%{}", self.defined);
+            self.defined += 1;
+            self.ids.insert(id, number.clone());
+            return format!("{} = {}", number, description);
+        }
+
+        description
+    }
+
+    fn describe(&self, inst: &Instruction) -> String {
+        let mut operands = Vec::new();
+
+        for o in &inst.operands {
+            operands.push(match *o {
+                Operand::IdRef(id) => self.id(id),
+                ref other => format!("{:?}", other),
+            });
+        }
+
+        let mut out = String::from(inst.class.opname);
+
+        if let Some(result_type) = inst.result_type {
+            out.push('<');
+            out.push_str(&self.id(result_type));
+            out.push('>');
+        }
+
+        out.push('(');
+        out.push_str(&operands.join(", "));
+        out.push(')');
+
+        // variables are identified by their decorations, since their names are ignored.
+        if inst.class.opcode == spirv::Op::Variable {
+            if let Some(decorations) = inst.result_id.and_then(|id| self.decorations.get(&id)) {
+                out.push_str(&format!(" [{}]", decorations.join(", ")));
+            }
+        }
+
+        out
+    }
+
+    fn id(&self, id: Word) -> String {
+        self.ids.get(&id).cloned().unwrap_or_else(
+            || format!("Unknown({})", id),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, load_bytes, Difference, Section};
+    use rspirv::binary::Assemble;
+    use rspirv::mr::{Builder, Instruction, Module, Operand};
+    use spirv::{self, Capability, Decoration, ExecutionModel, StorageClass, Word};
+
+    fn module() -> Module {
+        let mut b = Builder::new();
+        b.capability(Capability::Shader);
+
+        let float = b.type_float(32);
+        let one = b.constant_f32(float, 1.0);
+        let pointer = b.type_pointer(None, StorageClass::Output, float);
+        let output = b.variable(pointer, None, StorageClass::Output, None);
+        b.decorate(output, Decoration::Location, &[Operand::LiteralInt32(0)]);
+
+        let void = b.type_void();
+        let fn_type = b.type_function(void, &[]);
+        let main = b.begin_function(void, None, spirv::FUNCTION_CONTROL_NONE, fn_type)
+            .unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.store(output, one, None, &[]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+
+        b.entry_point(ExecutionModel::Vertex, main, String::from("main"), &[output]);
+        b.module()
+    }
+
+    /// Renumber all ids of the module, in the reverse order.
+    fn renumber(module: &mut Module) {
+        let renumber = |inst: &mut Instruction| {
+            let new = |id: Word| 1000 - id;
+
+            inst.result_type = inst.result_type.map(&new);
+            inst.result_id = inst.result_id.map(&new);
+
+            for operand in &mut inst.operands {
+                if let Operand::IdRef(ref mut id) = *operand {
+                    *id = new(*id);
+                }
+            }
+        };
+
+        for inst in module
+            .entry_points
+            .iter_mut()
+            .chain(module.execution_modes.iter_mut())
+            .chain(module.debugs.iter_mut())
+            .chain(module.annotations.iter_mut())
+            .chain(module.types_global_values.iter_mut())
+        {
+            renumber(inst);
+        }
+
+        for function in &mut module.functions {
+            for inst in function.def.iter_mut().chain(function.parameters.iter_mut()) {
+                renumber(inst);
+            }
+
+            for block in &mut function.basic_blocks {
+                for inst in block.label.iter_mut().chain(block.instructions.iter_mut()) {
+                    renumber(inst);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_renumbered_ids() {
+        let expected = module();
+        let mut actual = module();
+        renumber(&mut actual);
+
+        assert_ne!(
+            expected.types_global_values[0].result_id,
+            actual.types_global_values[0].result_id
+        );
+        assert_eq!(compare(&expected, &actual), vec![]);
+    }
+
+    #[test]
+    fn test_load_byte_swapped() {
+        let expected = module();
+
+        let bytes: Vec<u8> = expected
+            .assemble()
+            .into_iter()
+            .flat_map(|w| vec![(w >> 24) as u8, (w >> 16) as u8, (w >> 8) as u8, w as u8])
+            .collect();
+
+        let actual = load_bytes(&bytes).unwrap();
+        assert_eq!(compare(&expected, &actual), vec![]);
+    }
+
+    #[test]
+    fn test_removed_decoration() {
+        let expected = module();
+        let mut actual = module();
+        actual.annotations.clear();
+
+        let decorations: Vec<Difference> = compare(&expected, &actual)
+            .into_iter()
+            .filter(|d| match *d {
+                Difference::Missing(Section::Decorations, _) |
+                Difference::Extra(Section::Decorations, _) => true,
+                _ => false,
+            })
+            .collect();
+
+        assert_eq!(decorations.len(), 1);
+
+        match decorations[0] {
+            Difference::Missing(_, ref what) => assert!(what.contains("Location")),
+            ref other => panic!("unexpected difference: {}", other),
+        }
+    }
+}
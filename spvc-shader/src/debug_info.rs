@@ -0,0 +1,226 @@
+//! Post-processing of debug information in finished modules.
+
+use super::rspirv::mr::{Instruction, Module, Operand};
+use super::spirv::{self, Word};
+use std::collections::HashMap;
+
+const NON_SEMANTIC_DEBUG_INFO: &'static str = "NonSemantic.Shader.DebugInfo.100";
+
+// instructions of NonSemantic.Shader.DebugInfo.100.
+const DEBUG_COMPILATION_UNIT: u32 = 1;
+const DEBUG_SOURCE: u32 = 35;
+const DEBUG_LINE: u32 = 103;
+
+/// Order debug instructions as required by the specification, keeping the relative order of
+/// instructions in each group.
+pub fn sort_debugs(module: &mut Module) {
+    module.debugs.sort_by_key(|inst| match inst.class.opcode {
+        spirv::Op::String => 0,
+        spirv::Op::SourceExtension => 1,
+        spirv::Op::Source => 2,
+        spirv::Op::SourceContinued => 3,
+        _ => 4,
+    });
+}
+
+/// Allocates ids, and declares the types and constants used by debug instructions.
+struct Builder<'a> {
+    module: &'a mut Module,
+    bound: Word,
+    void_type: Option<Word>,
+    u32_type: Option<Word>,
+    constants: HashMap<u32, Word>,
+}
+
+impl<'a> Builder<'a> {
+    fn id(&mut self) -> Word {
+        let id = self.bound;
+        self.bound += 1;
+        id
+    }
+
+    fn global(
+        &mut self,
+        opcode: spirv::Op,
+        result_type: Option<Word>,
+        operands: Vec<Operand>,
+    ) -> Word {
+        let id = self.id();
+        self.module.types_global_values.push(
+            Instruction::new(opcode, result_type, Some(id), operands),
+        );
+        id
+    }
+
+    fn void_type(&mut self) -> Word {
+        if let Some(id) = self.void_type {
+            return id;
+        }
+
+        let id = self.global(spirv::Op::TypeVoid, None, vec![]);
+        self.void_type = Some(id);
+        id
+    }
+
+    fn u32_type(&mut self) -> Word {
+        if let Some(id) = self.u32_type {
+            return id;
+        }
+
+        let operands = vec![Operand::LiteralInt32(32), Operand::LiteralInt32(0)];
+        let id = self.global(spirv::Op::TypeInt, None, operands);
+        self.u32_type = Some(id);
+        id
+    }
+
+    fn constant(&mut self, value: u32) -> Word {
+        if let Some(id) = self.constants.get(&value) {
+            return *id;
+        }
+
+        let ty = self.u32_type();
+        let id = self.global(spirv::Op::Constant, Some(ty), vec![Operand::LiteralInt32(value)]);
+        self.constants.insert(value, id);
+        id
+    }
+}
+
+/// Replace `OpLine` instructions with their equivalent in `NonSemantic.Shader.DebugInfo.100`.
+///
+/// A `DebugSource` and `DebugCompilationUnit` is declared for each file referenced.
+pub fn to_non_semantic(module: &mut Module) {
+    let bound = module.header.as_ref().map(|h| h.bound).unwrap_or(1);
+
+    let mut void_type = None;
+    let mut u32_type = None;
+    let mut constants = HashMap::new();
+
+    for inst in &module.types_global_values {
+        let id = match inst.result_id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        match (inst.class.opcode, inst.operands.get(0), inst.operands.get(1)) {
+            (spirv::Op::TypeVoid, _, _) => void_type = Some(id),
+            (spirv::Op::TypeInt,
+             Some(&Operand::LiteralInt32(32)),
+             Some(&Operand::LiteralInt32(0))) => u32_type = Some(id),
+            _ => {}
+        }
+    }
+
+    for inst in &module.types_global_values {
+        if let (spirv::Op::Constant, Some(id), Some(&Operand::LiteralInt32(value))) =
+            (inst.class.opcode, inst.result_id, inst.operands.get(0))
+        {
+            if inst.result_type.is_some() && inst.result_type == u32_type {
+                constants.entry(value).or_insert(id);
+            }
+        }
+    }
+
+    let mut b = Builder {
+        module: module,
+        bound: bound,
+        void_type: void_type,
+        u32_type: u32_type,
+        constants: constants,
+    };
+
+    let set = b.id();
+
+    b.module.ext_inst_imports.push(Instruction::new(
+        spirv::Op::ExtInstImport,
+        None,
+        Some(set),
+        vec![Operand::LiteralString(NON_SEMANTIC_DEBUG_INFO.to_string())],
+    ));
+
+    // DebugSource for each file, by the id of its OpString.
+    let mut sources = HashMap::new();
+    let mut lines = Vec::new();
+
+    for (f, function) in b.module.functions.iter().enumerate() {
+        for (bb, block) in function.basic_blocks.iter().enumerate() {
+            for (i, inst) in block.instructions.iter().enumerate() {
+                if inst.class.opcode != spirv::Op::Line {
+                    continue;
+                }
+
+                if let (Some(&Operand::IdRef(file)),
+                        Some(&Operand::LiteralInt32(line)),
+                        Some(&Operand::LiteralInt32(column))) =
+                    (inst.operands.get(0), inst.operands.get(1), inst.operands.get(2))
+                {
+                    lines.push((f, bb, i, file, line, column));
+                }
+            }
+        }
+    }
+
+    for (f, bb, i, file, line, column) in lines {
+        let void_type = b.void_type();
+
+        let source = match sources.get(&file).cloned() {
+            Some(source) => source,
+            None => {
+                let source = b.global(
+                    spirv::Op::ExtInst,
+                    Some(void_type),
+                    vec![
+                        Operand::IdRef(set),
+                        Operand::LiteralExtInstInteger(DEBUG_SOURCE),
+                        Operand::IdRef(file),
+                    ],
+                );
+
+                let version = b.constant(100);
+                let dwarf_version = b.constant(5);
+                // the source language is unknown.
+                let language = b.constant(0);
+
+                b.global(
+                    spirv::Op::ExtInst,
+                    Some(void_type),
+                    vec![
+                        Operand::IdRef(set),
+                        Operand::LiteralExtInstInteger(DEBUG_COMPILATION_UNIT),
+                        Operand::IdRef(version),
+                        Operand::IdRef(dwarf_version),
+                        Operand::IdRef(source),
+                        Operand::IdRef(language),
+                    ],
+                );
+
+                sources.insert(file, source);
+                source
+            }
+        };
+
+        let line = b.constant(line);
+        let column = b.constant(column);
+        let id = b.id();
+
+        b.module.functions[f].basic_blocks[bb].instructions[i] = Instruction::new(
+            spirv::Op::ExtInst,
+            Some(void_type),
+            Some(id),
+            vec![
+                Operand::IdRef(set),
+                Operand::LiteralExtInstInteger(DEBUG_LINE),
+                Operand::IdRef(source),
+                Operand::IdRef(line),
+                Operand::IdRef(line),
+                Operand::IdRef(column),
+                Operand::IdRef(column),
+            ],
+        );
+    }
+
+    let bound = b.bound;
+
+    if let Some(ref mut header) = module.header {
+        header.bound = bound;
+    }
+}
@@ -0,0 +1,137 @@
+//! # Decorations of input and output variables
+
+use super::errors::*;
+use super::interface::Interface;
+use super::op::Op;
+use super::rspirv::mr::Operand;
+use super::shader::{Shader, ShaderKind};
+use super::spirv::{Capability, Decoration, Word};
+use super::spirv_type::SpirvType;
+use std::sync::Arc;
+
+/// How a variable is interpolated between stages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Interpolation {
+    /// Perspective-correct interpolation, the default.
+    Smooth,
+    /// No interpolation, the value is taken from the provoking vertex.
+    ///
+    /// Required for integer inputs of fragment shaders.
+    Flat,
+    /// Linear interpolation in screen space.
+    NoPerspective,
+}
+
+/// Where an interpolated variable is sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Sampling {
+    /// Sample at the pixel center, the default.
+    Center,
+    /// Sample at a location within the covered area of the pixel.
+    Centroid,
+    /// Sample once per sample, requires `SampleRateShading`.
+    Sample,
+}
+
+/// Decorations of an input or output variable, in addition to its location.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VarDecorations {
+    /// How the variable is interpolated.
+    pub interpolation: Interpolation,
+    /// Where the variable is sampled.
+    pub sampling: Sampling,
+    /// The first component of the location the variable occupies.
+    pub component: Option<u32>,
+    /// The blend equation index of a fragment output.
+    pub index: Option<u32>,
+    /// If the variable is invariant between invocations.
+    pub invariant: bool,
+}
+
+impl Default for VarDecorations {
+    fn default() -> VarDecorations {
+        VarDecorations {
+            interpolation: Interpolation::Smooth,
+            sampling: Sampling::Center,
+            component: None,
+            index: None,
+            invariant: false,
+        }
+    }
+}
+
+impl VarDecorations {
+    /// Register all decorations for the given variable.
+    pub(crate) fn register(&self, id: Word, shader: &mut Shader) {
+        match self.interpolation {
+            Interpolation::Smooth => {}
+            Interpolation::Flat => {
+                shader.builder.decorate(id, Decoration::Flat, &[]);
+            }
+            Interpolation::NoPerspective => {
+                shader.builder.decorate(id, Decoration::NoPerspective, &[]);
+            }
+        }
+
+        match self.sampling {
+            Sampling::Center => {}
+            Sampling::Centroid => {
+                shader.builder.decorate(id, Decoration::Centroid, &[]);
+            }
+            Sampling::Sample => {
+                shader.capability(Capability::SampleRateShading);
+                shader.builder.decorate(id, Decoration::Sample, &[]);
+            }
+        }
+
+        if let Some(component) = self.component {
+            shader.builder.decorate(
+                id,
+                Decoration::Component,
+                &[Operand::LiteralInt32(component)],
+            );
+        }
+
+        if let Some(index) = self.index {
+            shader.builder.decorate(
+                id,
+                Decoration::Index,
+                &[Operand::LiteralInt32(index)],
+            );
+        }
+
+        if self.invariant {
+            shader.builder.decorate(id, Decoration::Invariant, &[]);
+        }
+    }
+}
+
+/// Check if the given type is made up of integer components.
+fn is_integer(ty: &SpirvType) -> bool {
+    if let Some(vector) = ty.as_vector() {
+        return vector.component.as_unsigned_integer().is_some();
+    }
+
+    ty.as_unsigned_integer().is_some()
+}
+
+/// Check that the decorations of the interface are valid for the given kind of shader.
+pub(crate) fn check_interface(kind: ShaderKind, interface: &[Arc<Op>]) -> Result<()> {
+    if kind != ShaderKind::Fragment {
+        return Ok(());
+    }
+
+    for op in interface {
+        if let Some(Interface::Input(var)) = op.as_interface() {
+            if is_integer(var.pointer.pointee_type.as_ref()) &&
+                var.decorations.interpolation != Interpolation::Flat
+            {
+                return Err(ErrorKind::IntegerInputNotFlat(var.name.clone()).into());
+            }
+        }
+    }
+
+    Ok(())
+}
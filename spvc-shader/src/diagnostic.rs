@@ -0,0 +1,135 @@
+//! # Diagnostics for bad operations
+//!
+//! When a bad operation is registered, the chain of bad operations which caused it is collected
+//! into a tree, together with the location where each operation was constructed, the types of
+//! their arguments and the names of the variables involved.
+
+use super::node::Node;
+use super::op::Op;
+use super::source_location::SourceLocation;
+use std::fmt;
+use std::sync::Arc;
+
+/// A bad operation, and the arguments which caused it to fail.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Name of the operation.
+    pub op: &'static str,
+    /// Why the operation failed.
+    pub reason: &'static str,
+    /// Type the arguments were expected to have, if known.
+    pub expected: Option<String>,
+    /// Location in the source where the operation was constructed, if known.
+    pub location: Option<SourceLocation>,
+    /// Arguments of the operation.
+    pub arguments: Vec<Argument>,
+}
+
+/// An argument of a bad operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Argument {
+    /// Name of the operation producing the argument.
+    pub op: &'static str,
+    /// Type of the argument.
+    pub ty: String,
+    /// Name of the variable the argument was accessed from, if any.
+    pub variable: Option<String>,
+    /// Location in the source where the argument was constructed, if known.
+    pub location: Option<SourceLocation>,
+    /// If the argument is itself a bad operation, why it failed.
+    pub cause: Option<Diagnostic>,
+}
+
+impl Diagnostic {
+    /// The innermost bad operation, which is the first cause of this one.
+    pub fn root_cause(&self) -> &Diagnostic {
+        self.arguments
+            .iter()
+            .filter_map(|a| a.cause.as_ref())
+            .next()
+            .map(|c| c.root_cause())
+            .unwrap_or(self)
+    }
+
+    fn write_header(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(location) = self.location {
+            write!(fmt, "{}: ", location)?;
+        }
+
+        write!(fmt, "bad `{}`: {}", self.op, self.reason)?;
+
+        if let Some(ref expected) = self.expected {
+            write!(fmt, ", expected `{}`", expected)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_tree(&self, fmt: &mut fmt::Formatter, indent: &str) -> fmt::Result {
+        for (i, argument) in self.arguments.iter().enumerate() {
+            let last = i + 1 == self.arguments.len();
+            let (branch, next) = if last { ("└─ ", "   ") } else { ("├─ ", "│  ") };
+
+            write!(fmt, "\n{}{}", indent, branch)?;
+
+            match argument.cause {
+                Some(ref cause) => {
+                    cause.write_header(fmt)?;
+                    cause.write_tree(fmt, &format!("{}{}", indent, next))?;
+                }
+                None => write!(fmt, "{}", argument)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.write_header(fmt)?;
+        self.write_tree(fmt, "")
+    }
+}
+
+impl Argument {
+    /// Describe the given operation as an argument of a bad operation.
+    pub(crate) fn new(op: &Arc<Op>) -> Argument {
+        Argument {
+            op: op.node().name(),
+            ty: op.op_type().display(),
+            variable: variable(op.as_ref()),
+            location: op.location(),
+            cause: op.as_bad_op().map(|bad_op| bad_op.diagnostic(op.location())),
+        }
+    }
+}
+
+impl fmt::Display for Argument {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "`{}`", self.ty)?;
+
+        match self.variable {
+            Some(ref variable) => write!(fmt, " from `{}`", variable)?,
+            None => write!(fmt, " from {}", self.op)?,
+        }
+
+        if let Some(location) = self.location {
+            write!(fmt, " at {}", location)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Name of the variable an operation is, or accesses or loads.
+fn variable(op: &Op) -> Option<String> {
+    if let Some(interface) = op.as_interface() {
+        return Some(interface.name().to_string());
+    }
+
+    match op.node() {
+        Node::Access(base, _) | Node::Load(base) => variable(base.as_ref()),
+        _ => None,
+    }
+}
@@ -0,0 +1,58 @@
+//! # Support for the `shader!` macro
+//!
+//! These functions are called by code generated by the `glsl-shader-macro` crate, and are not
+//! intended to be used directly.
+
+use super::access::access;
+use super::op::Op;
+use super::ops::{BadOp, load};
+use super::source_location::SourceLocation;
+use super::struct_member::StructMember;
+use std::sync::Arc;
+
+/// Use the given operation as a value, loading it if it is a pointer.
+#[track_caller]
+pub fn value(op: Arc<Op>) -> Arc<Op> {
+    if op.op_type().as_pointer().is_some() {
+        return load(op);
+    }
+
+    op
+}
+
+/// Access a struct member by name.
+#[track_caller]
+pub fn member(op: Arc<Op>, name: &str) -> Arc<Op> {
+    let member = op.op_type().as_pointer().and_then(|pointer| {
+        pointer.pointee_type.as_struct().and_then(|st| {
+            st.members.iter().find(|m| m.name == name).map(|m| {
+                StructMember {
+                    name: m.name.clone(),
+                    ty: m.ty.clone(),
+                    index: m.index,
+                }
+            })
+        })
+    });
+
+    if let Some(member) = member {
+        return access(op, member);
+    }
+
+    let bad_op = BadOp::new("member", "no such struct member", vec![op]);
+    Arc::new(bad_op.with_location(SourceLocation::caller()))
+}
+
+/// Attribute a bad operation to the given location in the source of the shader.
+///
+/// Other operations are returned as they are.
+pub fn at(op: Arc<Op>, file: &'static str, line: u32, column: u32) -> Arc<Op> {
+    if let Some(bad_op) = op.as_bad_op() {
+        if bad_op.location().is_none() {
+            let location = SourceLocation::new(file, line, column);
+            return Arc::new(bad_op.with_location(location));
+        }
+    }
+
+    op
+}
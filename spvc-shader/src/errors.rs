@@ -1,7 +1,11 @@
 //! Errors for spvc
 #![allow(missing_docs)]
 
+use diagnostic::Diagnostic;
 use rspirv;
+use shader::ShaderKind;
+use spirv::StorageClass;
+use validate::ValidationError;
 
 error_chain! {
     foreign_links {
@@ -11,7 +15,17 @@ error_chain! {
     }
 
     errors {
-        BadOp(op: &'static str, reason: &'static str, arguments: Vec<String>) {
+        /// Bad operation, with the tree of bad operations which caused it.
+        BadOp(diagnostic: Diagnostic) {
+            description("bad operation")
+            display("{}", diagnostic)
+        }
+
+        /// Several bad operations, as found by `FunctionBuilder::validate`.
+        BadOps(diagnostics: Vec<Diagnostic>) {
+            description("bad operations")
+            display("{}",
+                    diagnostics.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("\n"))
         }
 
         NoObjectId {
@@ -30,5 +44,106 @@ error_chain! {
 
         IllegalInterfaceType {
         }
+
+        /// Two interface variables occupy overlapping locations.
+        LocationConflict(storage_class: StorageClass,
+                         location: u32,
+                         first: String,
+                         second: String) {
+            description("conflicting interface locations")
+            display("{:?} variables `{}` and `{}` overlap at location {}",
+                    storage_class, first, second, location)
+        }
+
+        /// Program has no stage of the given kind.
+        MissingStage(kind: ShaderKind) {
+            description("missing shader stage")
+            display("program has no {:?} stage", kind)
+        }
+
+        /// Input of a stage is not provided by the previous stage.
+        UnmatchedStageInput(name: String, location: u32) {
+            description("unmatched stage input")
+            display("no output matches input `{}` at location {}", name, location)
+        }
+
+        /// Output of a stage doesn't match the corresponding input of the next stage.
+        StageInterfaceMismatch(output: String, input: String, reason: &'static str) {
+            description("stage interface mismatch")
+            display("output `{}` doesn't match input `{}`: {}", output, input, reason)
+        }
+
+        /// Integer input of a fragment shader is not decorated as flat.
+        IntegerInputNotFlat(name: String) {
+            description("integer fragment input must be flat")
+            display("integer fragment input `{}` must use flat interpolation", name)
+        }
+
+        /// Operation doesn't have the type expected by a typed expression.
+        ExprTypeMismatch(expected: &'static str, actual: String) {
+            description("expression type mismatch")
+            display("expected expression of type `{}`, but was `{}`", expected, actual)
+        }
+
+        /// Interface variable has no location, and none could be assigned.
+        MissingLocation(name: String) {
+            description("missing interface location")
+            display("no location for variable `{}`", name)
+        }
+
+        /// Operation or type can't be expressed by a backend.
+        UnsupportedByBackend(backend: &'static str, what: String) {
+            description("unsupported by backend")
+            display("{}: unsupported: {}", backend, what)
+        }
+
+        /// Feature is not available for the target of the shader.
+        UnsupportedByTarget(what: String, requires: String) {
+            description("unsupported by target")
+            display("{} requires {}", what, requires)
+        }
+
+        /// No entry point with the given name.
+        MissingEntryPoint(name: String) {
+            description("missing entry point")
+            display("no entry point named `{}`", name)
+        }
+
+        /// Setting can only be changed before any entry points are added.
+        EntryPointsAdded(setting: &'static str) {
+            description("entry points already added")
+            display("{} must be set before adding entry points", setting)
+        }
+
+        /// Entry point with the same name and kind was already added.
+        DuplicateEntryPoint(name: String, kind: ShaderKind) {
+            description("duplicate entry point")
+            display("{:?} entry point `{}` already exists", kind, name)
+        }
+
+        /// Module violates rules of the SPIR-V specification.
+        Invalid(errors: Vec<ValidationError>) {
+            description("invalid module")
+            display("invalid module: {}",
+                    errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(", "))
+        }
+
+        /// SPIR-V bytecode could not be parsed.
+        ModuleParse(message: String) {
+            description("failed to parse module")
+            display("failed to parse module: {}", message)
+        }
+
+        /// Entry point uses an execution model which is not supported.
+        UnsupportedExecutionModel(model: String) {
+            description("unsupported execution model")
+            display("unsupported execution model: {}", model)
+        }
+
+        /// GLSL source could not be parsed, or uses unsupported features.
+        GlslParse(line: u32, column: u32, message: String) {
+            description("failed to parse glsl")
+            display("{}:{}: {}", line, column, message)
+        }
     }
 }
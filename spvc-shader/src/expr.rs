@@ -0,0 +1,317 @@
+//! # Typed expressions
+//!
+//! Operations are untyped, and type errors are only detected when a shader is built.
+//! `Expr<T>` wraps an operation together with its GLSL type, so that the Rust compiler checks
+//! that expressions like `projection * view * model * vec4(position, 1.0)` are well-typed while
+//! still lowering to the same operations.
+//!
+//! ```rust,ignore
+//! let position = Expr::<Vec3>::load(position.clone())?;
+//! let projection = Expr::<Mat4>::load(global.access_member(Global::projection()))?;
+//! main.op(expr::store(gl_position.clone(), projection * expr::vec4(position, 1.0)));
+//! ```
+
+use super::errors::*;
+use super::op::Op;
+use super::ops;
+use super::spirv_type::SpirvType;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::sync::Arc;
+
+/// A GLSL type which can be used in typed expressions.
+pub trait ExprType: 'static {
+    /// Type of the components of an expression of this type.
+    type Component: ExprType;
+
+    /// Number of components which can be accessed.
+    const COMPONENTS: u32;
+
+    /// Name of the type, as it would be written in GLSL.
+    const NAME: &'static str;
+
+    /// Check if the given SPIR-V type corresponds to this type.
+    fn matches(ty: &SpirvType) -> bool;
+}
+
+/// Types which support component-wise addition, subtraction and negation.
+pub trait Arithmetic: ExprType {}
+
+/// Square matrix types, which can be transposed.
+pub trait SquareMatrix: ExprType {}
+
+/// Describes the result of multiplying `Self` by `Rhs`.
+pub trait MulExpr<Rhs: ExprType>: ExprType {
+    /// Type of the result of the multiplication.
+    type Output: ExprType;
+}
+
+impl ExprType for f32 {
+    type Component = f32;
+    const COMPONENTS: u32 = 0;
+    const NAME: &'static str = "float";
+
+    fn matches(ty: &SpirvType) -> bool {
+        ty.as_float().is_some()
+    }
+}
+
+impl Arithmetic for f32 {}
+
+macro_rules! vector_type {
+    ($name:ident, $glsl:expr, $count:expr) => {
+    /// Typed GLSL vector.
+    #[derive(Debug, Clone, Copy)]
+    pub struct $name;
+
+    impl ExprType for $name {
+        type Component = f32;
+        const COMPONENTS: u32 = $count;
+        const NAME: &'static str = $glsl;
+
+        fn matches(ty: &SpirvType) -> bool {
+            if let Some(vector) = ty.as_vector() {
+                return vector.component.as_float().is_some() && vector.component_count == $count;
+            }
+
+            false
+        }
+    }
+
+    impl Arithmetic for $name {}
+
+    impl MulExpr<f32> for $name {
+        type Output = $name;
+    }
+
+    impl MulExpr<$name> for f32 {
+        type Output = $name;
+    }
+
+    impl MulExpr<$name> for $name {
+        type Output = $name;
+    }
+    };
+}
+
+macro_rules! matrix_type {
+    ($name:ident, $glsl:expr, $column:ident, $count:expr) => {
+    /// Typed GLSL square matrix.
+    #[derive(Debug, Clone, Copy)]
+    pub struct $name;
+
+    impl ExprType for $name {
+        type Component = $column;
+        const COMPONENTS: u32 = $count;
+        const NAME: &'static str = $glsl;
+
+        fn matches(ty: &SpirvType) -> bool {
+            if let Some(matrix) = ty.as_matrix() {
+                return $column::matches(matrix.column_type.as_ref()) &&
+                    matrix.column_count == $count;
+            }
+
+            false
+        }
+    }
+
+    impl SquareMatrix for $name {}
+
+    impl MulExpr<$name> for $name {
+        type Output = $name;
+    }
+
+    impl MulExpr<$column> for $name {
+        type Output = $column;
+    }
+
+    impl MulExpr<f32> for $name {
+        type Output = $name;
+    }
+
+    impl MulExpr<$name> for f32 {
+        type Output = $name;
+    }
+    };
+}
+
+impl MulExpr<f32> for f32 {
+    type Output = f32;
+}
+
+vector_type!(Vec2, "vec2", 2);
+vector_type!(Vec3, "vec3", 3);
+vector_type!(Vec4, "vec4", 4);
+matrix_type!(Mat3, "mat3", Vec3, 3);
+matrix_type!(Mat4, "mat4", Vec4, 4);
+
+/// An operation with a statically known type.
+pub struct Expr<T: ExprType> {
+    op: Arc<Op>,
+    marker: PhantomData<T>,
+}
+
+impl<T: ExprType> Expr<T> {
+    /// Wrap the given operation, checking that it has the expected type.
+    pub fn new(op: Arc<Op>) -> Result<Expr<T>> {
+        if !T::matches(op.op_type()) {
+            return Err(
+                ErrorKind::ExprTypeMismatch(T::NAME, op.op_type().display()).into(),
+            );
+        }
+
+        Ok(Expr::unchecked(op))
+    }
+
+    /// Load the value of the given pointer, checking that it has the expected type.
+    pub fn load(pointer: Arc<Op>) -> Result<Expr<T>> {
+        Expr::new(ops::load(pointer))
+    }
+
+    /// Wrap an operation whose type is already known to be correct.
+    fn unchecked(op: Arc<Op>) -> Expr<T> {
+        Expr {
+            op: op,
+            marker: PhantomData,
+        }
+    }
+
+    /// A component of a vector, or a column of a matrix.
+    ///
+    /// Returns `None` if the index is out of bounds.
+    #[track_caller]
+    pub fn get(&self, index: u32) -> Option<Expr<T::Component>> {
+        if index >= T::COMPONENTS {
+            return None;
+        }
+
+        Some(Expr::unchecked(ops::extract(self.op.clone(), index)))
+    }
+
+    /// A component of a vector, or a column of a matrix.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[track_caller]
+    pub fn at(&self, index: u32) -> Expr<T::Component> {
+        match self.get(index) {
+            Some(component) => component,
+            None => {
+                panic!(
+                    "index {} is out of bounds for `{}` with {} components",
+                    index,
+                    T::NAME,
+                    T::COMPONENTS
+                )
+            }
+        }
+    }
+
+    /// Access the underlying operation.
+    pub fn op(&self) -> &Arc<Op> {
+        &self.op
+    }
+
+    /// Convert into the underlying operation.
+    pub fn into_op(self) -> Arc<Op> {
+        self.op
+    }
+}
+
+impl<T: ExprType> Clone for Expr<T> {
+    fn clone(&self) -> Expr<T> {
+        Expr {
+            op: self.op.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ExprType> fmt::Debug for Expr<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Expr")
+            .field("type", &T::NAME)
+            .field("op", &self.op)
+            .finish()
+    }
+}
+
+impl<T: ExprType> From<Expr<T>> for Arc<Op> {
+    fn from(expr: Expr<T>) -> Arc<Op> {
+        expr.op
+    }
+}
+
+impl<L, R> Mul<Expr<R>> for Expr<L>
+where
+    L: MulExpr<R>,
+    R: ExprType,
+{
+    type Output = Expr<L::Output>;
+
+    fn mul(self, rhs: Expr<R>) -> Self::Output {
+        Expr::unchecked(ops::mul(self.op, rhs.op))
+    }
+}
+
+impl<L> Mul<f32> for Expr<L>
+where
+    L: MulExpr<f32>,
+{
+    type Output = Expr<L::Output>;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        self * constant(rhs)
+    }
+}
+
+impl<T: Arithmetic> Add for Expr<T> {
+    type Output = Expr<T>;
+
+    fn add(self, rhs: Expr<T>) -> Expr<T> {
+        Expr::unchecked(ops::add(self.op, rhs.op))
+    }
+}
+
+impl<T: Arithmetic> Sub for Expr<T> {
+    type Output = Expr<T>;
+
+    fn sub(self, rhs: Expr<T>) -> Expr<T> {
+        Expr::unchecked(ops::sub(self.op, rhs.op))
+    }
+}
+
+impl<T: Arithmetic> Neg for Expr<T> {
+    type Output = Expr<T>;
+
+    fn neg(self) -> Expr<T> {
+        Expr::unchecked(ops::neg(self.op))
+    }
+}
+
+/// A constant float expression.
+pub fn constant(value: f32) -> Expr<f32> {
+    Expr::unchecked(ops::constant_f32(value))
+}
+
+/// Corresponds to the GLSL constructor `vec3(v, z)`.
+pub fn vec3(vector: Expr<Vec2>, z: f32) -> Expr<Vec3> {
+    Expr::unchecked(ops::vec2_to_vec3(vector.op, z))
+}
+
+/// Corresponds to the GLSL constructor `vec4(v, w)`.
+pub fn vec4(vector: Expr<Vec3>, w: f32) -> Expr<Vec4> {
+    Expr::unchecked(ops::vec3_to_vec4(vector.op, w))
+}
+
+/// Transpose a square matrix.
+pub fn transpose<T: SquareMatrix>(matrix: Expr<T>) -> Expr<T> {
+    Expr::unchecked(ops::transpose(matrix.op))
+}
+
+/// Store the value of the expression in the given pointer.
+pub fn store<T: ExprType>(dest: Arc<Op>, value: Expr<T>) -> Arc<Op> {
+    ops::store(dest, value.op)
+}
@@ -1,18 +1,25 @@
+use super::backend;
 use super::errors::*;
+use super::node::Node;
 use super::op::Op;
 use super::op_key::OpKey;
 use super::shader::Shader;
 use super::spirv::{self, Word};
-use std::rc::Rc;
+use std::sync::Arc;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Function {
     pub name: String,
-    ops: Vec<Rc<Op>>,
-    return_op: Option<Rc<Op>>,
+    ops: Vec<Arc<Op>>,
+    return_op: Option<Arc<Op>>,
 }
 
 impl Function {
+    /// Operations of the function, in order.
+    pub(crate) fn ops(&self) -> &[Arc<Op>] {
+        &self.ops
+    }
+
     pub fn register_function(self, shader: &mut Shader) -> Result<Word> {
         let ops = self.ops;
 
@@ -52,6 +59,7 @@ impl Function {
         )?;
 
         let _label_start_fn = shader.builder.begin_basic_block(None)?;
+        shader.reset_line();
 
         for s in ops {
             s.op_id(shader)?;
@@ -76,7 +84,7 @@ impl Function {
 #[derive(Debug)]
 pub struct FunctionBuilder {
     name: String,
-    ops: Vec<Rc<Op>>,
+    ops: Vec<Arc<Op>>,
 }
 
 impl FunctionBuilder {
@@ -89,10 +97,28 @@ impl FunctionBuilder {
     }
 
     /// Add an operation to this function builder.
-    pub fn op(&mut self, op: Rc<Op>) {
+    pub fn op(&mut self, op: Arc<Op>) {
         self.ops.push(op);
     }
 
+    /// Check the operations added so far, reporting every bad operation rather than only the
+    /// first one found when the function is registered.
+    ///
+    /// Bad operations caused by other bad operations are reported as part of their diagnostic.
+    pub fn validate(&self) -> Result<()> {
+        let mut diagnostics = Vec::new();
+
+        backend::walk(&self.ops, |op| if let Node::Bad(bad_op) = op.node() {
+            diagnostics.push(bad_op.diagnostic(op.location()));
+        });
+
+        if diagnostics.is_empty() {
+            return Ok(());
+        }
+
+        Err(ErrorKind::BadOps(diagnostics).into())
+    }
+
     /// Create a function that returns void
     ///
     /// All previously appended operations will be added to the created function.
@@ -105,7 +131,7 @@ impl FunctionBuilder {
     }
 
     /// Create a function that returns the value of the given operation.
-    pub fn returns(self, return_op: Rc<Op>) -> Function {
+    pub fn returns(self, return_op: Arc<Op>) -> Function {
         Function {
             name: self.name,
             ops: self.ops,
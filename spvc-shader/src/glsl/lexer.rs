@@ -0,0 +1,167 @@
+use errors::*;
+
+/// A single token of GLSL source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// An identifier or keyword.
+    Ident(String),
+    /// A float literal.
+    Number(f32),
+    /// An integer literal, without a decimal point or suffix.
+    Integer(u32),
+    /// A single punctuation character.
+    Punct(char),
+}
+
+/// A token, with the position where it starts.
+#[derive(Debug, Clone)]
+pub struct Spanned {
+    pub token: Token,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// Split GLSL source into tokens.
+///
+/// Comments and preprocessor directives are skipped.
+pub fn tokenize(source: &str) -> Result<Vec<Spanned>> {
+    let chars: Vec<char> = source.chars().collect();
+
+    let mut out = Vec::new();
+    let mut pos = 0usize;
+    let mut line = 1u32;
+    let mut column = 1u32;
+    // if only whitespace has been seen on the current line.
+    let mut line_start = true;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+        let start = (line, column);
+        let offset = pos;
+
+        if c == '\n' {
+            pos += 1;
+            line += 1;
+            column = 1;
+            line_start = true;
+            continue;
+        }
+
+        if c.is_whitespace() {
+            pos += 1;
+            column += 1;
+            continue;
+        }
+
+        // skip preprocessor directives, like `#version 450`.
+        if c == '#' && line_start {
+            while pos < chars.len() && chars[pos] != '\n' {
+                pos += 1;
+            }
+
+            continue;
+        }
+
+        line_start = false;
+
+        if c == '/' && chars.get(pos + 1) == Some(&'/') {
+            while pos < chars.len() && chars[pos] != '\n' {
+                pos += 1;
+            }
+
+            continue;
+        }
+
+        if c == '/' && chars.get(pos + 1) == Some(&'*') {
+            pos += 2;
+            column += 2;
+
+            loop {
+                match (chars.get(pos), chars.get(pos + 1)) {
+                    (Some(&'*'), Some(&'/')) => {
+                        pos += 2;
+                        column += 2;
+                        break;
+                    }
+                    (Some(&'\n'), _) => {
+                        pos += 1;
+                        line += 1;
+                        column = 1;
+                    }
+                    (Some(_), _) => {
+                        pos += 1;
+                        column += 1;
+                    }
+                    (None, _) => {
+                        return Err(
+                            ErrorKind::GlslParse(start.0, start.1, "unterminated comment".into())
+                                .into(),
+                        )
+                    }
+                }
+            }
+
+            continue;
+        }
+
+        let token = if c.is_alphabetic() || c == '_' {
+            let begin = pos;
+
+            while pos < chars.len() && (chars[pos].is_alphanumeric() || chars[pos] == '_') {
+                pos += 1;
+            }
+
+            Token::Ident(chars[begin..pos].iter().collect())
+        } else if c.is_digit(10) ||
+                   (c == '.' && chars.get(pos + 1).map_or(false, |c| c.is_digit(10)))
+        {
+            let begin = pos;
+
+            while pos < chars.len() && (chars[pos].is_digit(10) || chars[pos] == '.') {
+                pos += 1;
+            }
+
+            let number: String = chars[begin..pos].iter().collect();
+            let mut float = number.contains('.');
+
+            // float suffix.
+            if pos < chars.len() && (chars[pos] == 'f' || chars[pos] == 'F') {
+                pos += 1;
+                float = true;
+            }
+
+            let bad_number = || {
+                ErrorKind::GlslParse(start.0, start.1, format!("bad number `{}`", number))
+            };
+
+            if float {
+                Token::Number(number.parse::<f32>().map_err(|_| bad_number())?)
+            } else {
+                Token::Integer(number.parse::<u32>().map_err(|_| bad_number())?)
+            }
+        } else {
+            match c {
+                '(' | ')' | '{' | '}' | ';' | ',' | '=' | '*' | '+' | '-' | '.' => {
+                    pos += 1;
+                    Token::Punct(c)
+                }
+                c => {
+                    return Err(
+                        ErrorKind::GlslParse(start.0, start.1, format!("unexpected `{}`", c))
+                            .into(),
+                    )
+                }
+            }
+        };
+
+        column += (pos - offset) as u32;
+
+        out.push(Spanned {
+            token: token,
+            line: start.0,
+            column: start.1,
+        });
+    }
+
+    Ok(out)
+}
@@ -0,0 +1,103 @@
+//! # GLSL frontend
+//!
+//! Parses a small subset of GLSL 450, and lowers it into the same operations that are used when
+//! building shaders programmatically.
+//!
+//! Supported are `in` and `out` variables with `layout` qualifiers and interpolation qualifiers,
+//! uniform blocks, and a `void main()` consisting of local declarations and assignments.
+//!
+//! ```rust,ignore
+//! let glsl = glsl::parse(source, ShaderKind::Vertex)?;
+//!
+//! let mut shader = Shader::new();
+//! glsl.into_shader(&mut shader)?;
+//! ```
+
+mod lexer;
+mod parser;
+
+use built_in_var::BuiltInVar;
+use errors::*;
+use function::{Function, FunctionBuilder};
+use input_var::InputVar;
+use op::Op;
+use output_var::OutputVar;
+use shader::{Shader, ShaderKind};
+use std::sync::Arc;
+use uniform_var::UniformVar;
+
+/// A parsed GLSL shader.
+#[derive(Debug)]
+pub struct GlslShader {
+    kind: ShaderKind,
+    inputs: Vec<Arc<InputVar>>,
+    outputs: Vec<Arc<OutputVar>>,
+    uniforms: Vec<Arc<UniformVar>>,
+    built_ins: Vec<Arc<BuiltInVar>>,
+    ops: Vec<Arc<Op>>,
+}
+
+impl GlslShader {
+    /// Kind of the shader.
+    pub fn kind(&self) -> ShaderKind {
+        self.kind
+    }
+
+    /// Input variables declared by the shader.
+    pub fn inputs(&self) -> &[Arc<InputVar>] {
+        &self.inputs
+    }
+
+    /// Output variables declared by the shader.
+    pub fn outputs(&self) -> &[Arc<OutputVar>] {
+        &self.outputs
+    }
+
+    /// Uniform blocks declared by the shader.
+    pub fn uniforms(&self) -> &[Arc<UniformVar>] {
+        &self.uniforms
+    }
+
+    /// The `main` function of the shader.
+    pub fn function(&self) -> Function {
+        let mut main = FunctionBuilder::new("main");
+
+        for op in &self.ops {
+            main.op(op.clone());
+        }
+
+        main.returns_void()
+    }
+
+    /// Interface of the shader, being all inputs, outputs and used built-in variables.
+    pub fn interface(&self) -> Vec<Arc<Op>> {
+        let mut out: Vec<Arc<Op>> = Vec::new();
+
+        for var in &self.inputs {
+            out.push(var.clone());
+        }
+
+        for var in &self.outputs {
+            out.push(var.clone());
+        }
+
+        for var in &self.built_ins {
+            out.push(var.clone());
+        }
+
+        out
+    }
+
+    /// Add the shader as an entry point to the given shader module.
+    pub fn into_shader(self, shader: &mut Shader) -> Result<()> {
+        let function = self.function();
+        let interface = self.interface();
+        shader.entry_point(self.kind, function, interface)
+    }
+}
+
+/// Parse GLSL source for a shader of the given kind.
+pub fn parse(source: &str, kind: ShaderKind) -> Result<GlslShader> {
+    let tokens = lexer::tokenize(source)?;
+    parser::Parser::new(&tokens, kind).parse()
+}
@@ -0,0 +1,669 @@
+use super::GlslShader;
+use super::lexer::{Spanned, Token};
+use built_in_var::BuiltInVar;
+use dsl;
+use errors::*;
+use input_var::InputVarBuilder;
+use op::Op;
+use ops;
+use output_var::OutputVarBuilder;
+use shader::ShaderKind;
+use spirv::{BuiltIn, StorageClass};
+use spirv_type::SpirvType;
+use std::collections::HashMap;
+use std::sync::Arc;
+use struct_member::StructMember;
+use types::{Bool, Float, Matrix, Struct, UnsignedInteger, Vector, vec4};
+use uniform_var::UniformVar;
+
+/// Name used for the source file when attributing bad operations.
+const SOURCE: &'static str = "<glsl>";
+
+/// A value of an expression.
+///
+/// Literals are kept separate, since constructors like `vec4(v, 1.0)` take them as constants.
+enum Value {
+    Op(Arc<Op>),
+    Lit(f32),
+}
+
+impl Value {
+    fn into_op(self) -> Arc<Op> {
+        match self {
+            Value::Op(op) => op,
+            Value::Lit(value) => ops::constant_f32(value),
+        }
+    }
+}
+
+/// Parses GLSL source, and lowers it into operations.
+pub struct Parser<'a> {
+    tokens: &'a [Spanned],
+    pos: usize,
+    shader: GlslShader,
+    /// Variables and locals in scope.
+    scope: HashMap<String, Arc<Op>>,
+    /// Uniform blocks without an instance name, whose members are in scope.
+    anonymous: Vec<Arc<Op>>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(tokens: &'a [Spanned], kind: ShaderKind) -> Parser<'a> {
+        Parser {
+            tokens: tokens,
+            pos: 0,
+            shader: GlslShader {
+                kind: kind,
+                inputs: Vec::new(),
+                outputs: Vec::new(),
+                uniforms: Vec::new(),
+                built_ins: Vec::new(),
+                ops: Vec::new(),
+            },
+            scope: HashMap::new(),
+            anonymous: Vec::new(),
+        }
+    }
+
+    /// Parse all declarations and the main function.
+    pub fn parse(mut self) -> Result<GlslShader> {
+        while self.pos < self.tokens.len() {
+            if self.is_ident("void") {
+                self.function()?;
+            } else {
+                self.declaration()?;
+            }
+        }
+
+        Ok(self.shader)
+    }
+
+    fn error<T, M: Into<String>>(&self, message: M) -> Result<T> {
+        let (line, column) = self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map(|t| (t.line, t.column))
+            .unwrap_or((1, 1));
+
+        Err(ErrorKind::GlslParse(line, column, message.into()).into())
+    }
+
+    /// Attribute bad operations to the token at the given position.
+    fn at(&self, op: Arc<Op>, pos: usize) -> Arc<Op> {
+        match self.tokens.get(pos) {
+            Some(t) => dsl::at(op, SOURCE, t.line, t.column),
+            None => op,
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.token)
+    }
+
+    fn is_ident(&self, name: &str) -> bool {
+        match self.peek() {
+            Some(&Token::Ident(ref ident)) => ident == name,
+            _ => false,
+        }
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        let ident = match self.peek() {
+            Some(&Token::Ident(ref ident)) => ident.clone(),
+            _ => return self.error("expected identifier"),
+        };
+
+        self.pos += 1;
+        Ok(ident)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<()> {
+        if !self.is_ident(keyword) {
+            return self.error(format!("expected `{}`", keyword));
+        }
+
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn punct(&mut self, c: char) -> Result<()> {
+        if !self.is_punct(c) {
+            return self.error(format!("expected `{}`", c));
+        }
+
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn integer(&mut self) -> Result<u32> {
+        let value = match self.peek() {
+            Some(&Token::Integer(value)) => value,
+            _ => return self.error("expected integer"),
+        };
+
+        self.pos += 1;
+        Ok(value)
+    }
+
+    /// Parse a type name.
+    fn ty(&mut self) -> Result<Arc<SpirvType>> {
+        let ty: Arc<SpirvType> = match self.peek() {
+            Some(&Token::Ident(ref name)) => {
+                match name.as_str() {
+                    "bool" => Arc::new(Bool),
+                    "float" => Arc::new(Float),
+                    "uint" => Arc::new(UnsignedInteger),
+                    "vec2" => Arc::new(Vector::new(Float, 2)),
+                    "vec3" => Arc::new(Vector::new(Float, 3)),
+                    "vec4" => Arc::new(Vector::new(Float, 4)),
+                    "uvec2" => Arc::new(Vector::new(UnsignedInteger, 2)),
+                    "uvec3" => Arc::new(Vector::new(UnsignedInteger, 3)),
+                    "uvec4" => Arc::new(Vector::new(UnsignedInteger, 4)),
+                    "mat3" => Arc::new(Matrix::new(Vector::new(Float, 3), 3)),
+                    "mat4" => Arc::new(Matrix::new(Vector::new(Float, 4), 4)),
+                    name => return self.error(format!("unsupported type `{}`", name)),
+                }
+            }
+            _ => return self.error("expected type"),
+        };
+
+        self.pos += 1;
+        Ok(ty)
+    }
+
+    /// Parse `layout(a = 1, b = 2)`.
+    ///
+    /// Of the qualifiers without a value, only the default memory layouts of blocks are accepted.
+    fn layout(&mut self) -> Result<HashMap<String, u32>> {
+        let mut out = HashMap::new();
+
+        if !self.is_ident("layout") {
+            return Ok(out);
+        }
+
+        self.pos += 1;
+        self.punct('(')?;
+
+        loop {
+            let pos = self.pos;
+            let name = self.ident()?;
+
+            if self.is_punct('=') {
+                self.pos += 1;
+                let value = self.integer()?;
+                out.insert(name, value);
+            } else {
+                match name.as_str() {
+                    "std140" | "std430" | "column_major" => {}
+                    other => {
+                        self.pos = pos;
+                        return self.error(format!("unsupported layout qualifier `{}`", other));
+                    }
+                }
+            }
+
+            if self.is_punct(',') {
+                self.pos += 1;
+                continue;
+            }
+
+            break;
+        }
+
+        self.punct(')')?;
+        Ok(out)
+    }
+
+    /// Parse a global declaration of an input, output or uniform block.
+    fn declaration(&mut self) -> Result<()> {
+        let layout = self.layout()?;
+        let mut qualifiers = Vec::new();
+
+        while let Some(&Token::Ident(ref ident)) = self.peek() {
+            match ident.as_str() {
+                "smooth" | "flat" | "noperspective" | "centroid" | "sample" | "invariant" => {
+                    qualifiers.push(ident.clone());
+                }
+                _ => break,
+            }
+
+            self.pos += 1;
+        }
+
+        let storage = self.ident()?;
+
+        match storage.as_str() {
+            "in" => {
+                let ty = self.ty()?;
+                let name = self.ident()?;
+                self.punct(';')?;
+
+                let mut builder = InputVarBuilder::new(name.as_str(), ty);
+
+                if let Some(location) = layout.get("location") {
+                    builder = builder.location(*location);
+                }
+
+                if let Some(component) = layout.get("component") {
+                    builder = builder.component(*component);
+                }
+
+                for q in &qualifiers {
+                    builder = match q.as_str() {
+                        "flat" => builder.flat(),
+                        "noperspective" => builder.no_perspective(),
+                        "centroid" => builder.centroid(),
+                        "sample" => builder.sample(),
+                        _ => builder,
+                    };
+                }
+
+                let var = builder.build();
+                self.scope.insert(name, var.clone());
+                self.shader.inputs.push(var);
+            }
+            "out" => {
+                let ty = self.ty()?;
+                let name = self.ident()?;
+                self.punct(';')?;
+
+                let mut builder = OutputVarBuilder::new(name.as_str(), ty);
+
+                if let Some(location) = layout.get("location") {
+                    builder = builder.location(*location);
+                }
+
+                if let Some(component) = layout.get("component") {
+                    builder = builder.component(*component);
+                }
+
+                if let Some(index) = layout.get("index") {
+                    builder = builder.index(*index);
+                }
+
+                for q in &qualifiers {
+                    builder = match q.as_str() {
+                        "flat" => builder.flat(),
+                        "noperspective" => builder.no_perspective(),
+                        "centroid" => builder.centroid(),
+                        "sample" => builder.sample(),
+                        "invariant" => builder.invariant(),
+                        _ => builder,
+                    };
+                }
+
+                let var = builder.build();
+                self.scope.insert(name, var.clone());
+                self.shader.outputs.push(var);
+            }
+            "uniform" => {
+                let block_pos = self.pos;
+                let block = self.ident()?;
+                self.punct('{')?;
+
+                let mut members = Vec::new();
+
+                while !self.is_punct('}') {
+                    let ty = self.ty()?;
+                    let name = self.ident()?;
+                    self.punct(';')?;
+
+                    let index = members.len() as u32;
+                    members.push(Arc::new(StructMember::new(name, ty, index)));
+                }
+
+                self.punct('}')?;
+
+                let instance = if self.is_punct(';') {
+                    None
+                } else {
+                    Some(self.ident()?)
+                };
+
+                self.punct(';')?;
+
+                let ty = Struct::new(block.clone(), members);
+
+                // blocks without a binding would silently share the first one.
+                let binding = match layout.get("binding") {
+                    Some(binding) => *binding,
+                    None => {
+                        self.pos = block_pos;
+                        return self.error(format!("uniform block `{}` has no binding", block));
+                    }
+                };
+
+                let set = layout.get("set").cloned().unwrap_or(0);
+
+                let var = UniformVar::from_type(
+                    instance.as_ref().unwrap_or(&block).as_str(),
+                    Arc::new(ty),
+                    set,
+                    binding,
+                );
+
+                match instance {
+                    Some(instance) => {
+                        self.scope.insert(instance, var.clone());
+                    }
+                    None => {
+                        self.anonymous.push(var.clone());
+                    }
+                }
+
+                self.shader.uniforms.push(var);
+            }
+            other => return self.error(format!("unsupported storage qualifier `{}`", other)),
+        }
+
+        Ok(())
+    }
+
+    /// Parse `void main() { ... }`.
+    fn function(&mut self) -> Result<()> {
+        self.keyword("void")?;
+        self.keyword("main")?;
+        self.punct('(')?;
+        self.punct(')')?;
+        self.punct('{')?;
+
+        while !self.is_punct('}') {
+            self.statement()?;
+        }
+
+        self.punct('}')?;
+        Ok(())
+    }
+
+    fn statement(&mut self) -> Result<()> {
+        let is_decl = match (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) {
+            (Some(a), Some(b)) => {
+                match (&a.token, &b.token) {
+                    (&Token::Ident(_), &Token::Ident(_)) => true,
+                    _ => false,
+                }
+            }
+            _ => false,
+        };
+
+        if is_decl {
+            // local variables are bound directly to the value they are initialized with.
+            self.ty()?;
+            let name = self.ident()?;
+            self.punct('=')?;
+            let value = self.expr()?.into_op();
+            self.punct(';')?;
+            self.scope.insert(name, value);
+            return Ok(());
+        }
+
+        let start = self.pos;
+        let name = self.ident()?;
+
+        if self.is_punct('=') && self.is_local(&name) {
+            self.punct('=')?;
+            let value = self.expr()?.into_op();
+            self.punct(';')?;
+            self.scope.insert(name, value);
+            return Ok(());
+        }
+
+        self.pos = start;
+        let dest = self.postfix()?.into_op();
+        let eq = self.pos;
+        self.punct('=')?;
+        let source = dsl::value(self.expr()?.into_op());
+        self.punct(';')?;
+
+        let op = self.at(ops::store(dest, source), eq);
+        self.shader.ops.push(op);
+        Ok(())
+    }
+
+    /// Check if the given name refers to a local value, rather than a variable.
+    fn is_local(&self, name: &str) -> bool {
+        match self.scope.get(name) {
+            Some(op) => op.as_interface().is_none(),
+            None => false,
+        }
+    }
+
+    fn expr(&mut self) -> Result<Value> {
+        let mut lhs = self.term()?;
+
+        while self.is_punct('+') || self.is_punct('-') {
+            let pos = self.pos;
+            let add = self.is_punct('+');
+            self.pos += 1;
+
+            let rhs = self.term()?;
+            let lhs_op = dsl::value(lhs.into_op());
+            let rhs_op = dsl::value(rhs.into_op());
+
+            let op = if add {
+                ops::add(lhs_op, rhs_op)
+            } else {
+                ops::sub(lhs_op, rhs_op)
+            };
+
+            lhs = Value::Op(self.at(op, pos));
+        }
+
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Value> {
+        let mut lhs = self.unary()?;
+
+        while self.is_punct('*') {
+            let pos = self.pos;
+            self.pos += 1;
+
+            let rhs = self.unary()?;
+            let op = ops::mul(dsl::value(lhs.into_op()), dsl::value(rhs.into_op()));
+            lhs = Value::Op(self.at(op, pos));
+        }
+
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Value> {
+        if self.is_punct('-') {
+            let pos = self.pos;
+            self.pos += 1;
+
+            return Ok(match self.unary()? {
+                Value::Lit(value) => Value::Lit(-value),
+                Value::Op(op) => Value::Op(self.at(ops::neg(dsl::value(op)), pos)),
+            });
+        }
+
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Value> {
+        let mut value = self.primary()?;
+
+        while self.is_punct('.') {
+            self.pos += 1;
+            let pos = self.pos;
+            let member = self.ident()?;
+            let op = dsl::member(value.into_op(), member.as_str());
+            value = Value::Op(self.at(op, pos));
+        }
+
+        Ok(value)
+    }
+
+    fn primary(&mut self) -> Result<Value> {
+        if let Some(&Token::Number(value)) = self.peek() {
+            self.pos += 1;
+            return Ok(Value::Lit(value));
+        }
+
+        // integers are converted, like in `vec4(position, 1)`.
+        if let Some(&Token::Integer(value)) = self.peek() {
+            self.pos += 1;
+            return Ok(Value::Lit(value as f32));
+        }
+
+        if self.is_punct('(') {
+            self.pos += 1;
+            let value = self.expr()?;
+            self.punct(')')?;
+            return Ok(value);
+        }
+
+        let pos = self.pos;
+        let name = self.ident()?;
+
+        if self.is_punct('(') {
+            return self.call(name, pos);
+        }
+
+        if let Some(op) = self.scope.get(&name) {
+            return Ok(Value::Op(op.clone()));
+        }
+
+        if let Some(op) = self.built_in(&name) {
+            return Ok(Value::Op(op));
+        }
+
+        for block in &self.anonymous {
+            let member = dsl::member(block.clone(), name.as_str());
+
+            if member.as_bad_op().is_none() {
+                return Ok(Value::Op(member));
+            }
+        }
+
+        self.pos = pos;
+        self.error(format!("unknown identifier `{}`", name))
+    }
+
+    /// Parse a call to a built-in function or constructor.
+    fn call(&mut self, name: String, pos: usize) -> Result<Value> {
+        self.punct('(')?;
+
+        let mut args = Vec::new();
+
+        while !self.is_punct(')') {
+            args.push(self.expr()?);
+
+            if !self.is_punct(')') {
+                self.punct(',')?;
+            }
+        }
+
+        self.punct(')')?;
+
+        let mut args = args.into_iter();
+
+        let op = match (name.as_str(), args.next(), args.next(), args.next(), args.next()) {
+            ("transpose", Some(m), None, None, None) => ops::transpose(dsl::value(m.into_op())),
+            ("inverse", Some(m), None, None, None) => ops::inverse(dsl::value(m.into_op())),
+            ("mat3", Some(m), None, None, None) => ops::mat4_to_mat3(dsl::value(m.into_op())),
+            ("vec3", Some(v), Some(Value::Lit(z)), None, None) => {
+                ops::vec2_to_vec3(dsl::value(v.into_op()), z)
+            }
+            ("vec4", Some(v), Some(Value::Lit(w)), None, None) => {
+                ops::vec3_to_vec4(dsl::value(v.into_op()), w)
+            }
+            ("vec4", Some(v), Some(Value::Lit(z)), Some(Value::Lit(w)), None) => {
+                ops::vec2_to_vec4(dsl::value(v.into_op()), z, w)
+            }
+            (name, ..) => {
+                self.pos = pos;
+                return self.error(format!("unsupported call to `{}`", name));
+            }
+        };
+
+        Ok(Value::Op(self.at(op, pos)))
+    }
+
+    /// Look up, or declare, a built-in variable.
+    fn built_in(&mut self, name: &str) -> Option<Arc<Op>> {
+        if let Some(var) = self.shader.built_ins.iter().find(|v| v.name() == name) {
+            return Some(var.clone());
+        }
+
+        let var = match (self.shader.kind, name) {
+            (ShaderKind::Vertex, "gl_Position") => {
+                BuiltInVar::new(name, vec4(), StorageClass::Output, BuiltIn::Position)
+            }
+            (ShaderKind::Vertex, "gl_PointSize") => {
+                BuiltInVar::new(name, Float, StorageClass::Output, BuiltIn::PointSize)
+            }
+            (ShaderKind::Fragment, "gl_FragCoord") => {
+                BuiltInVar::new(name, vec4(), StorageClass::Input, BuiltIn::FragCoord)
+            }
+            _ => return None,
+        };
+
+        self.shader.built_ins.push(var.clone());
+        Some(var)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse;
+    use shader::ShaderKind;
+
+    #[test]
+    fn test_parse_in_vert() {
+        let glsl = parse(include_str!("../../../in.vert"), ShaderKind::Vertex).unwrap();
+
+        let inputs: Vec<_> = glsl.inputs().iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(inputs, vec!["position", "normal", "tex_coord"]);
+
+        let outputs: Vec<_> = glsl.outputs().iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(outputs, vec!["v_normal", "v_tex_coord"]);
+
+        let uniforms: Vec<_> = glsl.uniforms().iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(uniforms, vec!["model", "global"]);
+
+        assert_eq!(glsl.function().ops().len(), 3);
+    }
+
+    #[test]
+    fn test_parse_std140_block() {
+        let source = "
+            layout(std140, set = 1, binding = 2) uniform Global {
+                mat4 view;
+            } global;
+
+            void main() {}
+        ";
+
+        let glsl = parse(source, ShaderKind::Vertex).unwrap();
+        let uniforms = glsl.uniforms();
+
+        assert_eq!(uniforms.len(), 1);
+        assert_eq!(uniforms[0].name, "global");
+        assert_eq!(uniforms[0].set, 1);
+        assert_eq!(uniforms[0].binding, 2);
+    }
+
+    #[test]
+    fn test_missing_binding() {
+        let source = "layout(set = 0) uniform Global { mat4 view; } global;";
+        assert!(parse(source, ShaderKind::Vertex).is_err());
+    }
+
+    #[test]
+    fn test_fractional_location() {
+        let source = "layout(location = 1.5) in vec3 position;";
+        assert!(parse(source, ShaderKind::Vertex).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_layout_qualifier() {
+        let source = "layout(row_major) uniform Global { mat4 view; } global;";
+        assert!(parse(source, ShaderKind::Vertex).is_err());
+    }
+}
@@ -1,5 +1,7 @@
+use super::decorations::{Interpolation, Sampling, VarDecorations};
 use super::errors::*;
 use super::interface::Interface;
+use super::node::Node;
 use super::op::Op;
 use super::op_key::OpKey;
 use super::pointer::Pointer;
@@ -8,7 +10,7 @@ use super::rspirv::mr::Operand;
 use super::shader::Shader;
 use super::spirv::{Decoration, StorageClass};
 use super::spirv_type::{SpirvType, WrapperType};
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Reflection of an input variable.
 #[derive(Debug)]
@@ -17,8 +19,12 @@ pub struct InputVar {
     pub name: String,
     /// Type of the input variable, packed into a pointer.
     pub pointer: Pointer,
-    /// Location of the input variable.
-    pub location: u32,
+    /// Explicit location of the input variable.
+    ///
+    /// If `None`, a location is assigned when the variable is used in an entry point.
+    pub location: Option<u32>,
+    /// Decorations of the input variable, in addition to its location.
+    pub decorations: VarDecorations,
 }
 
 impl WrapperType for InputVar {
@@ -40,13 +46,20 @@ impl Op for InputVar {
         &self.pointer
     }
 
+    fn node(&self) -> Node {
+        Node::Input(self)
+    }
+
     fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
         let variable_type = self.pointer.register_type(shader)?;
+        let location = shader.locations.resolve(Interface::Input(self))?;
 
         let id = shader.cache_op(
             OpKey::InputVar {
                 variable_type: variable_type,
-                location: self.location,
+                location: location,
+                name: self.name.clone(),
+                decorations: self.decorations.clone(),
             },
             |s| {
                 let variable_id = s.builder.variable(
@@ -61,9 +74,11 @@ impl Op for InputVar {
                 s.builder.decorate(
                     variable_id,
                     Decoration::Location,
-                    &[Operand::LiteralInt32(self.location)],
+                    &[Operand::LiteralInt32(location)],
                 );
 
+                self.decorations.register(variable_id, s);
+
                 Ok(variable_id)
             },
         )?;
@@ -74,11 +89,44 @@ impl Op for InputVar {
 
 impl InputVar {
     /// Create a new input variable.
-    pub fn new<T: 'static + SpirvType>(name: &str, ty: T, location: u32) -> Rc<InputVar> {
-        Rc::new(InputVar {
+    pub fn new<T: 'static + SpirvType>(name: &str, ty: T, location: u32) -> Arc<InputVar> {
+        Arc::new(InputVar {
+            name: String::from(name),
+            pointer: Pointer::new(StorageClass::Input, Arc::new(ty)),
+            location: Some(location),
+            decorations: VarDecorations::default(),
+        })
+    }
+
+    /// Create a builder for an input variable with additional decorations.
+    pub fn builder<T: 'static + SpirvType>(name: &str, ty: T) -> InputVarBuilder {
+        InputVarBuilder::new(name, Arc::new(ty))
+    }
+
+    /// Create a new input variable, without an explicit location.
+    ///
+    /// Requires the shader to use `LocationMode::Auto`.
+    pub fn auto<T: 'static + SpirvType>(name: &str, ty: T) -> Arc<InputVar> {
+        Arc::new(InputVar {
             name: String::from(name),
-            pointer: Pointer::new(StorageClass::Input, Rc::new(ty)),
+            pointer: Pointer::new(StorageClass::Input, Arc::new(ty)),
+            location: None,
+            decorations: VarDecorations::default(),
+        })
+    }
+
+    /// Create a new input variable with an already shared type.
+    pub(crate) fn from_type(
+        name: &str,
+        ty: Arc<SpirvType>,
+        location: Option<u32>,
+        decorations: VarDecorations,
+    ) -> Arc<InputVar> {
+        Arc::new(InputVar {
+            name: String::from(name),
+            pointer: Pointer::new(StorageClass::Input, ty),
             location: location,
+            decorations: decorations,
         })
     }
 
@@ -101,3 +149,72 @@ impl InputVar {
         })
     }
 }
+
+/// Builder of input variables.
+#[derive(Debug)]
+pub struct InputVarBuilder {
+    name: String,
+    ty: Arc<SpirvType>,
+    location: Option<u32>,
+    decorations: VarDecorations,
+}
+
+impl InputVarBuilder {
+    /// Create a new builder, with an already shared type.
+    pub(crate) fn new(name: &str, ty: Arc<SpirvType>) -> InputVarBuilder {
+        InputVarBuilder {
+            name: String::from(name),
+            ty: ty,
+            location: None,
+            decorations: VarDecorations::default(),
+        }
+    }
+
+    /// Set the location of the variable.
+    ///
+    /// If not set, the shader must use `LocationMode::Auto`.
+    pub fn location(mut self, location: u32) -> InputVarBuilder {
+        self.location = Some(location);
+        self
+    }
+
+    /// Use flat interpolation, required for integer variables passed to fragment shaders.
+    pub fn flat(mut self) -> InputVarBuilder {
+        self.decorations.interpolation = Interpolation::Flat;
+        self
+    }
+
+    /// Use linear interpolation in screen space.
+    pub fn no_perspective(mut self) -> InputVarBuilder {
+        self.decorations.interpolation = Interpolation::NoPerspective;
+        self
+    }
+
+    /// Sample the variable within the covered area of the pixel.
+    pub fn centroid(mut self) -> InputVarBuilder {
+        self.decorations.sampling = Sampling::Centroid;
+        self
+    }
+
+    /// Sample the variable once per sample.
+    pub fn sample(mut self) -> InputVarBuilder {
+        self.decorations.sampling = Sampling::Sample;
+        self
+    }
+
+    /// Set the first component of the location occupied by the variable.
+    pub fn component(mut self, component: u32) -> InputVarBuilder {
+        self.decorations.component = Some(component);
+        self
+    }
+
+    /// Build the input variable.
+    pub fn build(self) -> Arc<InputVar> {
+        Arc::new(InputVar {
+            name: self.name,
+            pointer: Pointer::new(StorageClass::Input, self.ty),
+            location: self.location,
+            decorations: self.decorations,
+        })
+    }
+}
@@ -1,11 +1,69 @@
+use super::backend::walk;
+use super::built_in_var::BuiltInVar;
 use super::input_var::InputVar;
+use super::op::Op;
 use super::output_var::OutputVar;
 use super::uniform_var::UniformVar;
+use std::sync::Arc;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub enum Interface<'a> {
     Input(&'a InputVar),
     Output(&'a OutputVar),
     Uniform(&'a UniformVar),
-    BuiltIn,
+    BuiltIn(&'a BuiltInVar),
+}
+
+impl<'a> Interface<'a> {
+    /// Name of the variable.
+    pub fn name(&self) -> &'a str {
+        match *self {
+            Interface::Input(var) => var.name.as_str(),
+            Interface::Output(var) => var.name.as_str(),
+            Interface::Uniform(var) => var.name.as_str(),
+            Interface::BuiltIn(var) => var.name(),
+        }
+    }
+
+    /// Explicit location of the variable, if it is an input or output with one.
+    pub fn location(&self) -> Option<u32> {
+        match *self {
+            Interface::Input(var) => var.location,
+            Interface::Output(var) => var.location,
+            _ => None,
+        }
+    }
+
+    /// Identity of the variable, which is unique while it is alive.
+    pub(crate) fn id(&self) -> usize {
+        match *self {
+            Interface::Input(var) => var as *const InputVar as usize,
+            Interface::Output(var) => var as *const OutputVar as usize,
+            Interface::Uniform(var) => var as *const UniformVar as usize,
+            Interface::BuiltIn(var) => var as *const BuiltInVar as usize,
+        }
+    }
+}
+
+/// Collect the input, output and built-in variables used by the given operations, in order of
+/// first use.
+///
+/// With `all_globals`, uniform variables are collected as well, as required by SPIR-V 1.4.
+pub fn collect(ops: &[Arc<Op>], all_globals: bool) -> Vec<Arc<Op>> {
+    let mut out = Vec::new();
+
+    walk(ops, |op| match op.as_interface() {
+        Some(Interface::Input(_)) |
+        Some(Interface::Output(_)) |
+        Some(Interface::BuiltIn(_)) => out.push(op.clone()),
+        Some(Interface::Uniform(_)) if all_globals => out.push(op.clone()),
+        _ => {}
+    });
+
+    out
+}
+
+/// Check if two operations are the same variable.
+pub fn same_var(a: &Arc<Op>, b: &Arc<Op>) -> bool {
+    &**a as *const Op as *const () == &**b as *const Op as *const ()
 }
@@ -4,10 +4,19 @@
 
 #[cfg(feature = "vulkan")]
 extern crate vulkano;
+#[cfg(feature = "ash")]
+extern crate ash;
 extern crate rspirv;
 extern crate spirv_headers as spirv;
 #[macro_use]
 extern crate error_chain;
+#[macro_use]
+extern crate log;
+#[cfg(feature = "serialize")]
+extern crate serde;
+#[cfg(feature = "serialize")]
+#[macro_use]
+extern crate serde_derive;
 
 mod access;
 mod function;
@@ -26,19 +35,51 @@ mod matrix_dims;
 mod vector_dims;
 mod interface;
 mod op_key;
+mod node;
+mod locations;
+mod source_location;
+mod located;
+mod debug_info;
 pub mod errors;
+pub mod backend;
+pub mod decorations;
 pub mod struct_member;
+pub mod program;
+pub mod expr;
+pub mod validate;
+pub mod reflect;
+pub mod cache;
+pub mod compare;
+pub mod diagnostic;
+pub mod optimize;
+pub mod target;
+#[cfg(feature = "glsl")]
+pub mod glsl;
+#[cfg(feature = "ash")]
+pub mod ash_layout;
+#[doc(hidden)]
+pub mod dsl;
 
 pub use self::access::AccessTrait;
 pub use self::built_in_var::BuiltInVar;
+pub use self::cache::Cache;
+pub use self::decorations::{Interpolation, Sampling, VarDecorations};
+pub use self::expr::Expr;
 pub use self::function::FunctionBuilder;
-pub use self::input_var::InputVar;
+pub use self::input_var::{InputVar, InputVarBuilder};
+pub use self::locations::LocationMode;
+pub use self::op::Op;
 // FIXME: Too many to list explicitly.
 pub use self::ops::*;
-pub use self::output_var::OutputVar;
-pub use self::shader::{Shader, ShaderKind};
+pub use self::output_var::{OutputVar, OutputVarBuilder};
+pub use self::program::Program;
+pub use self::shader::{DebugInfo, Shader, ShaderKind};
+pub use self::source_location::SourceLocation;
+pub use self::spirv_type::TypeKey;
 pub use self::spirv::BuiltIn;
 pub use self::spirv::StorageClass;
 pub use self::struct_member::StructMember;
-pub use self::types::{Bool, Float, Matrix, Struct, Vector, mat3, mat4, st, vec2, vec3, vec4};
+pub use self::target::{Env, Target};
+pub use self::types::{Bool, Float, Matrix, Struct, UnsignedInteger, Vector, mat3, mat4, st, vec2,
+                      vec3, vec4};
 pub use self::uniform_var::UniformVar;
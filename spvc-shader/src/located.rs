@@ -0,0 +1,93 @@
+use super::errors::*;
+use super::interface::Interface;
+use super::node::Node;
+use super::op::Op;
+use super::ops::BadOp;
+use super::reg_op::RegOp;
+use super::shader::Shader;
+use super::source_location::SourceLocation;
+use super::spirv::{StorageClass, Word};
+use super::spirv_type::SpirvType;
+use std::sync::Arc;
+
+/// An operation, together with the location in the source where it was constructed.
+#[derive(Debug)]
+pub struct Located<T> {
+    op: T,
+    location: SourceLocation,
+}
+
+/// Wrap the given operation with the location of the code calling the current function.
+#[track_caller]
+pub fn located<T: 'static + Op>(op: T) -> Arc<Op> {
+    Arc::new(Located {
+        op: op,
+        location: SourceLocation::caller(),
+    })
+}
+
+impl<T: Op> Op for Located<T> {
+    fn base(&self) -> Option<&Arc<Op>> {
+        self.op.base()
+    }
+
+    fn access_chain(&self) -> Option<&[u32]> {
+        self.op.access_chain()
+    }
+
+    fn storage_class(&self) -> Option<StorageClass> {
+        self.op.storage_class()
+    }
+
+    fn op_type(&self) -> &SpirvType {
+        self.op.op_type()
+    }
+
+    fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
+        if let Some(bad_op) = self.op.as_bad_op() {
+            return Err(bad_op.error_at(Some(self.location)));
+        }
+
+        let op = self.op.register_op(shader)?;
+
+        if !shader.emits_lines() {
+            return Ok(op);
+        }
+
+        Ok(Box::new(RegisteredLocated {
+            op: op,
+            location: self.location,
+        }))
+    }
+
+    fn node(&self) -> Node {
+        self.op.node()
+    }
+
+    fn location(&self) -> Option<SourceLocation> {
+        Some(self.location)
+    }
+
+    fn as_bad_op(&self) -> Option<&BadOp> {
+        self.op.as_bad_op()
+    }
+
+    fn as_interface(&self) -> Option<Interface> {
+        self.op.as_interface()
+    }
+}
+
+#[derive(Debug)]
+pub struct RegisteredLocated {
+    op: Box<RegOp>,
+    location: SourceLocation,
+}
+
+impl RegOp for RegisteredLocated {
+    fn op_id(&self, shader: &mut Shader) -> Result<Option<Word>> {
+        shader.push_line(self.location);
+        let id = self.op.op_id(shader);
+        shader.pop_line();
+        id
+    }
+}
@@ -0,0 +1,189 @@
+use super::errors::*;
+use super::interface::Interface;
+use super::op::Op;
+use super::spirv::StorageClass;
+use super::spirv_type::SpirvType;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// How locations of input and output variables are determined when adding entry points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocationMode {
+    /// Every input and output variable must be given an explicit location.
+    Explicit,
+    /// Variables without an explicit location are assigned the lowest free locations, in the order
+    /// they are declared in the entry point interface.
+    Auto,
+}
+
+/// Locations resolved for interface variables, keyed by the identity of the variable.
+#[derive(Debug, Default)]
+pub struct Locations {
+    assigned: HashMap<usize, u32>,
+}
+
+/// A single variable taking part in location allocation.
+struct Slot<'a> {
+    id: usize,
+    name: &'a str,
+    location: Option<u32>,
+    /// Range of components occupied in each location.
+    components: (u32, u32),
+    count: u32,
+}
+
+impl<'a> Slot<'a> {
+    fn new(var: Interface<'a>, ty: &SpirvType, component: Option<u32>) -> Slot<'a> {
+        // variables without a component decoration start at the first component.
+        let first = component.unwrap_or(0);
+
+        let scalar = ty.as_float().is_some() || ty.as_unsigned_integer().is_some() ||
+            ty.as_bool().is_some();
+
+        // matrices and structs occupy whole locations.
+        let component_count = match ty.as_vector() {
+            Some(vector) => vector.component_count,
+            None if scalar => 1,
+            None => 4,
+        };
+
+        Slot {
+            id: var.id(),
+            name: var.name(),
+            location: var.location(),
+            components: (first, first + component_count),
+            count: ty.location_slots(),
+        }
+    }
+}
+
+/// A range of locations which is occupied by a variable.
+struct Used<'a> {
+    start: u32,
+    end: u32,
+    components: (u32, u32),
+    id: usize,
+    name: &'a str,
+}
+
+impl Locations {
+    /// Look up the location of the given variable.
+    ///
+    /// Explicit locations always take precedence over assigned ones.
+    pub fn resolve(&self, var: Interface) -> Result<u32> {
+        if let Some(location) = var.location() {
+            return Ok(location);
+        }
+
+        self.assigned
+            .get(&var.id())
+            .cloned()
+            .ok_or_else(|| ErrorKind::MissingLocation(var.name().to_string()).into())
+    }
+
+    /// Assign locations to all input and output variables in the given interface, and check that
+    /// no two variables occupy the same location.
+    pub fn assign(&mut self, mode: LocationMode, interface: &[Arc<Op>]) -> Result<()> {
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+
+        for op in interface {
+            match op.as_interface() {
+                Some(Interface::Input(var)) => {
+                    let ty = var.pointer.pointee_type.as_ref();
+                    inputs.push(Slot::new(Interface::Input(var), ty, var.decorations.component));
+                }
+                Some(Interface::Output(var)) => {
+                    let ty = var.pointer.pointee_type.as_ref();
+                    outputs.push(Slot::new(Interface::Output(var), ty, var.decorations.component));
+                }
+                _ => continue,
+            }
+        }
+
+        self.assign_class(mode, StorageClass::Input, inputs)?;
+        self.assign_class(mode, StorageClass::Output, outputs)?;
+        Ok(())
+    }
+
+    fn assign_class(
+        &mut self,
+        mode: LocationMode,
+        storage_class: StorageClass,
+        slots: Vec<Slot>,
+    ) -> Result<()> {
+        let mut used: Vec<Used> = Vec::new();
+        let mut pending = Vec::new();
+
+        for slot in slots {
+            let location = match slot.location.or_else(|| self.assigned.get(&slot.id).cloned()) {
+                Some(location) => location,
+                None => {
+                    pending.push(slot);
+                    continue;
+                }
+            };
+
+            let end = location + slot.count;
+
+            for other in &used {
+                if other.id == slot.id {
+                    continue;
+                }
+
+                // variables packed into different components may share a location.
+                let (a_start, a_end) = other.components;
+                let (b_start, b_end) = slot.components;
+
+                if location < other.end && other.start < end && a_start < b_end &&
+                    b_start < a_end
+                {
+                    return Err(
+                        ErrorKind::LocationConflict(
+                            storage_class,
+                            location.max(other.start),
+                            other.name.to_string(),
+                            slot.name.to_string(),
+                        ).into(),
+                    );
+                }
+            }
+
+            used.push(Used {
+                start: location,
+                end: end,
+                components: slot.components,
+                id: slot.id,
+                name: slot.name,
+            });
+        }
+
+        for slot in pending {
+            if mode == LocationMode::Explicit {
+                return Err(ErrorKind::MissingLocation(slot.name.to_string()).into());
+            }
+
+            let mut location = 0u32;
+
+            // find the lowest location where the whole variable fits.
+            while let Some(other) = used.iter().find(|u| {
+                location < u.end && u.start < location + slot.count
+            })
+            {
+                location = other.end;
+            }
+
+            used.push(Used {
+                start: location,
+                end: location + slot.count,
+                components: slot.components,
+                id: slot.id,
+                name: slot.name,
+            });
+
+            self.assigned.insert(slot.id, location);
+        }
+
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use super::types::{Float, Matrix, Vector};
 use super::vector_dims::VectorDims;
 
 /// Reflects the dimensions of a matrix, and how various operations affect them.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MatrixDims {
     cols: u32,
     rows: u32,
@@ -0,0 +1,89 @@
+use super::built_in_var::BuiltInVar;
+use super::input_var::InputVar;
+use super::op::Op;
+use super::ops::BadOp;
+use super::output_var::OutputVar;
+use super::uniform_var::UniformVar;
+use std::sync::Arc;
+
+/// Structure of an operation, as seen by backends other than SPIR-V.
+#[derive(Debug, Clone)]
+pub enum Node<'a> {
+    /// An input variable.
+    Input(&'a InputVar),
+    /// An output variable.
+    Output(&'a OutputVar),
+    /// A uniform variable.
+    Uniform(&'a UniformVar),
+    /// A built-in variable.
+    BuiltIn(&'a BuiltInVar),
+    /// Access into struct members of a variable, by member index.
+    Access(&'a Arc<Op>, &'a [u32]),
+    /// Load the value of a pointer.
+    Load(&'a Arc<Op>),
+    /// Store a value (second) in a pointer (first).
+    Store(&'a Arc<Op>, &'a Arc<Op>),
+    /// Component-wise addition.
+    Add(&'a Arc<Op>, &'a Arc<Op>),
+    /// Component-wise subtraction.
+    Sub(&'a Arc<Op>, &'a Arc<Op>),
+    /// Multiplication, as the GLSL `*` operator.
+    Mul(&'a Arc<Op>, &'a Arc<Op>),
+    /// Component-wise negation.
+    Neg(&'a Arc<Op>),
+    /// Extract a component of a vector, or a column of a matrix.
+    Extract(&'a Arc<Op>, u32),
+    /// A float constant.
+    ConstantF32(f32),
+    /// Expand a vector with constant components.
+    Expand(&'a Arc<Op>, Vec<f32>),
+    /// Transpose a matrix.
+    Transpose(&'a Arc<Op>),
+    /// Invert a matrix.
+    Inverse(&'a Arc<Op>),
+    /// Take the upper-left 3x3 of a 4x4 matrix.
+    Mat4ToMat3(&'a Arc<Op>),
+    /// A bad operation.
+    Bad(&'a BadOp),
+}
+
+impl<'a> Node<'a> {
+    /// Operations that this node directly depends on.
+    pub fn children(&self) -> Vec<&'a Arc<Op>> {
+        use self::Node::*;
+
+        match *self {
+            Input(_) | Output(_) | Uniform(_) | BuiltIn(_) | ConstantF32(_) | Bad(_) => vec![],
+            Access(base, _) => vec![base],
+            Load(op) | Neg(op) | Extract(op, _) | Expand(op, _) | Transpose(op) |
+            Inverse(op) | Mat4ToMat3(op) => vec![op],
+            Store(lhs, rhs) | Add(lhs, rhs) | Sub(lhs, rhs) | Mul(lhs, rhs) => vec![lhs, rhs],
+        }
+    }
+
+    /// Short name of the operation, for diagnostics.
+    pub fn name(&self) -> &'static str {
+        use self::Node::*;
+
+        match *self {
+            Input(_) => "input",
+            Output(_) => "output",
+            Uniform(_) => "uniform",
+            BuiltIn(_) => "built-in",
+            Access(..) => "access",
+            Load(_) => "load",
+            Store(..) => "store",
+            Add(..) => "add",
+            Sub(..) => "sub",
+            Mul(..) => "mul",
+            Neg(_) => "neg",
+            Extract(..) => "extract",
+            ConstantF32(_) => "constant",
+            Expand(..) => "expand",
+            Transpose(_) => "transpose",
+            Inverse(_) => "inverse",
+            Mat4ToMat3(_) => "mat4_to_mat3",
+            Bad(bad_op) => bad_op.op_name(),
+        }
+    }
+}
@@ -1,36 +1,57 @@
 use super::errors::*;
 use super::interface::Interface;
+use super::node::Node;
 use super::ops::BadOp;
 use super::reg_op::RegOp;
 use super::shader::Shader;
+use super::source_location::SourceLocation;
 use super::spirv::StorageClass;
 use super::spirv_type::SpirvType;
 use std::fmt;
-use std::rc::Rc;
+use std::sync::Arc;
 
-pub trait Op: fmt::Debug {
+/// An operation in the graph of a shader.
+///
+/// Operations are registered lazily when the function or entry point using them is added to a
+/// shader.
+///
+/// Operations are immutable once constructed, so graphs of them can be shared between threads.
+pub trait Op: fmt::Debug + Send + Sync {
     /// If this is an access operation, returns the base being accessed.
-    fn base(&self) -> Option<&Rc<Op>> {
+    fn base(&self) -> Option<&Arc<Op>> {
         None
     }
 
+    /// If this is an access operation, returns the indexes of the members being accessed.
     fn access_chain(&self) -> Option<&[u32]> {
         None
     }
 
+    /// Storage class of the pointer produced by this operation, if any.
     fn storage_class(&self) -> Option<StorageClass> {
         None
     }
 
+    /// Type of the result of the operation.
     fn op_type(&self) -> &SpirvType;
 
+    /// Register the operation, and everything it depends on, with the given shader.
     fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>>;
 
+    /// Structure of the operation, used by backends other than SPIR-V.
+    fn node(&self) -> Node;
+
+    /// Location in the source where the operation was constructed, if known.
+    fn location(&self) -> Option<SourceLocation> {
+        None
+    }
+
     /// Convert this op to a bad op, if it is one.
     fn as_bad_op(&self) -> Option<&BadOp> {
         None
     }
 
+    /// Convert this op to an interface variable, if it is one.
     fn as_interface(&self) -> Option<Interface> {
         None
     }
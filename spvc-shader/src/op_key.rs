@@ -1,4 +1,6 @@
+use super::decorations::VarDecorations;
 use super::spirv::{BuiltIn, StorageClass, Word};
+use super::spirv_type::TypeKey;
 
 /// Description of a SPIR-V type, used as a lookup key to avoid duplicate declarations.
 #[derive(PartialEq, Eq, Hash, Debug)]
@@ -11,10 +13,8 @@ pub enum OpKey {
         component_type: Word,
         component_count: u32,
     },
-    Struct {
-        name: String,
-        field_types: Vec<Word>,
-    },
+    /// Structs are identified by their structure, since their members are decorated.
+    Struct { key: TypeKey },
     Matrix {
         column_type: Word,
         column_count: u32,
@@ -27,8 +27,19 @@ pub enum OpKey {
         return_type: Word,
         parameter_types: Vec<Word>,
     },
-    InputVar { variable_type: Word, location: u32 },
-    OutputVar { variable_type: Word, location: u32 },
+    /// Variables are only shared if they have the same name and decorations.
+    InputVar {
+        variable_type: Word,
+        location: u32,
+        name: String,
+        decorations: VarDecorations,
+    },
+    OutputVar {
+        variable_type: Word,
+        location: u32,
+        name: String,
+        decorations: VarDecorations,
+    },
     BuiltInVar {
         variable_type: Word,
         built_in: BuiltIn,
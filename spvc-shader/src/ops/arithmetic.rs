@@ -0,0 +1,113 @@
+use super::BadOp;
+use super::binary::float_type;
+use errors::*;
+use located::located;
+use node::Node;
+use op::Op;
+use reg_op::RegOp;
+use shader::Shader;
+use spirv::Word;
+use spirv_type::SpirvType;
+use std::sync::Arc;
+
+binary_op!(Add, RegAdd, f_add, Add);
+binary_op!(Sub, RegSub, f_sub, Sub);
+
+/// Perform a component-wise addition of the two arguments.
+///
+/// Both arguments must be floats, or float vectors of the same size.
+#[track_caller]
+pub fn add(lhs: Arc<Op>, rhs: Arc<Op>) -> Arc<Op> {
+    let op_type = match float_type(lhs.op_type()) {
+        Some(op_type) => op_type,
+        None => return located(BadOp::new("add", "expected float or vector", vec![lhs, rhs])),
+    };
+
+    if op_type.matches(rhs.op_type()) {
+        return located(Add {
+            op_type: op_type,
+            lhs: lhs,
+            rhs: rhs,
+        });
+    }
+
+    let expected = op_type.display();
+    located(BadOp::new("add", "argument type mismatch", vec![lhs, rhs]).expecting(expected))
+}
+
+/// Perform a component-wise subtraction of the two arguments.
+///
+/// Both arguments must be floats, or float vectors of the same size.
+#[track_caller]
+pub fn sub(lhs: Arc<Op>, rhs: Arc<Op>) -> Arc<Op> {
+    let op_type = match float_type(lhs.op_type()) {
+        Some(op_type) => op_type,
+        None => return located(BadOp::new("sub", "expected float or vector", vec![lhs, rhs])),
+    };
+
+    if op_type.matches(rhs.op_type()) {
+        return located(Sub {
+            op_type: op_type,
+            lhs: lhs,
+            rhs: rhs,
+        });
+    }
+
+    let expected = op_type.display();
+    located(BadOp::new("sub", "argument type mismatch", vec![lhs, rhs]).expecting(expected))
+}
+
+#[derive(Debug)]
+pub struct Neg {
+    op_type: Arc<SpirvType>,
+    value: Arc<Op>,
+}
+
+/// Negate the argument.
+///
+/// Expects a float, or a float vector.
+#[track_caller]
+pub fn neg(value: Arc<Op>) -> Arc<Op> {
+    if let Some(op_type) = float_type(value.op_type()) {
+        return located(Neg {
+            op_type: op_type,
+            value: value,
+        });
+    }
+
+    located(BadOp::new("neg", "expected float or vector", vec![value]))
+}
+
+impl Op for Neg {
+    fn op_type(&self) -> &SpirvType {
+        self.op_type.as_ref()
+    }
+
+    fn node(&self) -> Node {
+        Node::Neg(&self.value)
+    }
+
+    fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
+        let result_type = self.op_type.register_type(shader)?;
+        let value = self.value.register_op(shader)?;
+
+        Ok(Box::new(RegNeg {
+            result_type: result_type,
+            value: value,
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct RegNeg {
+    result_type: Word,
+    value: Box<RegOp>,
+}
+
+impl RegOp for RegNeg {
+    fn op_id(&self, shader: &mut Shader) -> Result<Option<Word>> {
+        let value = self.value.op_id(shader)?.ok_or(ErrorKind::NoObjectId)?;
+        let id = shader.builder.f_negate(self.result_type, None, value)?;
+        Ok(Some(id))
+    }
+}
@@ -1,10 +1,12 @@
+use diagnostic::{Argument, Diagnostic};
 use errors::*;
+use node::Node;
 use op::Op;
 use reg_op::RegOp;
 use shader::Shader;
+use source_location::SourceLocation;
 use spirv_type::{NoType, SpirvType};
-use std::collections::LinkedList;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Reflects a bad operation.
 ///
@@ -15,53 +17,87 @@ pub struct BadOp {
     op_type: NoType,
     op_name: &'static str,
     reason: &'static str,
-    causes: Vec<Rc<Op>>,
+    expected: Option<String>,
+    causes: Vec<Arc<Op>>,
+    location: Option<SourceLocation>,
 }
 
 impl BadOp {
     /// Create a new bad operation.
-    pub fn new(op_name: &'static str, reason: &'static str, causes: Vec<Rc<Op>>) -> BadOp {
+    pub fn new(op_name: &'static str, reason: &'static str, causes: Vec<Arc<Op>>) -> BadOp {
         BadOp {
             op_type: NoType,
             op_name: op_name,
             reason: reason,
+            expected: None,
             causes: causes,
+            location: None,
         }
     }
-}
 
-impl Op for BadOp {
-    fn op_type(&self) -> &SpirvType {
-        &self.op_type
+    /// Record the type the arguments were expected to have.
+    pub fn expecting(mut self, expected: String) -> BadOp {
+        self.expected = Some(expected);
+        self
     }
 
-    fn register_op(&self, _: &mut Shader) -> Result<Box<RegOp>> {
-        let mut current = self;
+    /// Name of the operation which failed.
+    pub fn op_name(&self) -> &'static str {
+        self.op_name
+    }
 
-        let mut queue: LinkedList<&BadOp> = LinkedList::new();
-        queue.push_back(self);
+    /// Location in the source code where the bad operation was constructed, if known.
+    pub fn location(&self) -> Option<SourceLocation> {
+        self.location
+    }
 
-        while let Some(next) = queue.pop_front() {
-            if let Some(bad_op) = next.as_bad_op() {
-                current = bad_op;
+    /// Create a copy of this bad operation, attributed to the given location.
+    pub fn with_location(&self, location: SourceLocation) -> BadOp {
+        BadOp {
+            op_type: NoType,
+            op_name: self.op_name,
+            reason: self.reason,
+            expected: self.expected.clone(),
+            causes: self.causes.clone(),
+            location: Some(location),
+        }
+    }
 
-                for c in &bad_op.causes {
-                    if let Some(bad_op) = c.as_bad_op() {
-                        queue.push_back(bad_op);
-                    }
-                }
-            }
+    /// Describe this bad operation, and the bad operations which caused it.
+    ///
+    /// `location` is where the operation was constructed, if this bad operation doesn't know it.
+    pub fn diagnostic(&self, location: Option<SourceLocation>) -> Diagnostic {
+        Diagnostic {
+            op: self.op_name,
+            reason: self.reason,
+            expected: self.expected.clone(),
+            location: self.location.or(location),
+            arguments: self.causes.iter().map(Argument::new).collect(),
         }
+    }
 
-        let arguments: Vec<String> = current
-            .causes
-            .iter()
-            .map(|r| r.op_type().display())
-            .collect();
+    /// The error for this bad operation, constructed at the given location.
+    pub(crate) fn error_at(&self, location: Option<SourceLocation>) -> Error {
+        ErrorKind::BadOp(self.diagnostic(location)).into()
+    }
+
+    /// The error for this bad operation.
+    pub(crate) fn error(&self) -> Error {
+        self.error_at(None)
+    }
+}
+
+impl Op for BadOp {
+    fn op_type(&self) -> &SpirvType {
+        &self.op_type
+    }
 
-        Err(
-            ErrorKind::BadOp(current.op_name, current.reason, arguments).into(),
-        )
+    fn node(&self) -> Node {
+        Node::Bad(self)
+    }
+
+    fn register_op(&self, _: &mut Shader) -> Result<Box<RegOp>> {
+        Err(self.error())
     }
 
     fn as_bad_op(&self) -> Option<&Self> {
@@ -0,0 +1,81 @@
+/// Declare a binary operation, and its registered counterpart, which is emitted using the given
+/// builder method, and is represented by the given node.
+macro_rules! binary_op {
+    ($st:ident, $reg:ident, $method:ident, $node:ident) => {
+    #[allow(missing_docs)]
+    #[derive(Debug)]
+    pub struct $st {
+        op_type: ::std::sync::Arc<SpirvType>,
+        lhs: ::std::sync::Arc<Op>,
+        rhs: ::std::sync::Arc<Op>,
+    }
+
+    impl Op for $st {
+        fn op_type(&self) -> &SpirvType {
+            self.op_type.as_ref()
+        }
+
+        fn node(&self) -> Node {
+            Node::$node(&self.lhs, &self.rhs)
+        }
+
+        fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
+            let result_type = self.op_type.register_type(shader)?;
+
+            let lhs = self.lhs.register_op(shader)?;
+            let rhs = self.rhs.register_op(shader)?;
+
+            Ok(Box::new($reg {
+                result_type: result_type,
+                lhs: lhs,
+                rhs: rhs,
+            }))
+        }
+    }
+
+    #[allow(missing_docs)]
+    #[derive(Debug)]
+    pub struct $reg {
+        result_type: Word,
+        lhs: Box<RegOp>,
+        rhs: Box<RegOp>,
+    }
+
+    impl RegOp for $reg {
+        fn op_id(&self, shader: &mut Shader) -> Result<Option<Word>> {
+            let lhs = self.lhs.op_id(shader)?.ok_or(ErrorKind::NoObjectId)?;
+            let rhs = self.rhs.op_id(shader)?.ok_or(ErrorKind::NoObjectId)?;
+
+            let id = shader.builder.$method(
+                self.result_type,
+                None,
+                lhs,
+                rhs,
+            )?;
+
+            Ok(Some(id))
+        }
+    }
+    };
+}
+
+use spirv_type::SpirvType;
+use std::sync::Arc;
+use types::Float;
+
+/// Reflects the type of a float, or a vector of floats, as an owned type.
+///
+/// These are the only types which support component-wise arithmetic.
+pub fn float_type(ty: &SpirvType) -> Option<Arc<SpirvType>> {
+    if ty.as_float().is_some() {
+        return Some(Arc::new(Float));
+    }
+
+    if let Some(vector) = ty.as_vector() {
+        if vector.component.as_float().is_some() {
+            return Some(Arc::new(vector));
+        }
+    }
+
+    None
+}
@@ -0,0 +1,76 @@
+//! Variants of the operations which fail as soon as they are constructed with bad arguments,
+//! rather than when the function using them is registered.
+
+use super::{add, extract, inverse, load, mat4_to_mat3, mul, neg, store, sub, transpose};
+use errors::*;
+use op::Op;
+use std::sync::Arc;
+
+/// Return the operation, or the error describing it if it is a bad operation.
+pub fn check(op: Arc<Op>) -> Result<Arc<Op>> {
+    if let Some(bad_op) = op.as_bad_op() {
+        return Err(bad_op.error_at(op.location()));
+    }
+
+    Ok(op)
+}
+
+/// Like `add`, but fails immediately on bad arguments.
+#[track_caller]
+pub fn try_add(lhs: Arc<Op>, rhs: Arc<Op>) -> Result<Arc<Op>> {
+    check(add(lhs, rhs))
+}
+
+/// Like `sub`, but fails immediately on bad arguments.
+#[track_caller]
+pub fn try_sub(lhs: Arc<Op>, rhs: Arc<Op>) -> Result<Arc<Op>> {
+    check(sub(lhs, rhs))
+}
+
+/// Like `neg`, but fails immediately on a bad argument.
+#[track_caller]
+pub fn try_neg(value: Arc<Op>) -> Result<Arc<Op>> {
+    check(neg(value))
+}
+
+/// Like `mul`, but fails immediately on bad arguments.
+#[track_caller]
+pub fn try_mul(lhs: Arc<Op>, rhs: Arc<Op>) -> Result<Arc<Op>> {
+    check(mul(lhs, rhs))
+}
+
+/// Like `load`, but fails immediately on a bad argument.
+#[track_caller]
+pub fn try_load(object: Arc<Op>) -> Result<Arc<Op>> {
+    check(load(object))
+}
+
+/// Like `store`, but fails immediately on bad arguments.
+#[track_caller]
+pub fn try_store(dest: Arc<Op>, source: Arc<Op>) -> Result<Arc<Op>> {
+    check(store(dest, source))
+}
+
+/// Like `extract`, but fails immediately on bad arguments.
+#[track_caller]
+pub fn try_extract(composite: Arc<Op>, index: u32) -> Result<Arc<Op>> {
+    check(extract(composite, index))
+}
+
+/// Like `transpose`, but fails immediately on a bad argument.
+#[track_caller]
+pub fn try_transpose(matrix: Arc<Op>) -> Result<Arc<Op>> {
+    check(transpose(matrix))
+}
+
+/// Like `inverse`, but fails immediately on a bad argument.
+#[track_caller]
+pub fn try_inverse(matrix: Arc<Op>) -> Result<Arc<Op>> {
+    check(inverse(matrix))
+}
+
+/// Like `mat4_to_mat3`, but fails immediately on a bad argument.
+#[track_caller]
+pub fn try_mat4_to_mat3(matrix: Arc<Op>) -> Result<Arc<Op>> {
+    check(mat4_to_mat3(matrix))
+}
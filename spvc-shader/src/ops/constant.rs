@@ -0,0 +1,38 @@
+use errors::*;
+use located::located;
+use node::Node;
+use op::Op;
+use reg_op::RegOp;
+use shader::Shader;
+use spirv_type::SpirvType;
+use std::sync::Arc;
+use types::Float;
+
+#[derive(Debug)]
+pub struct ConstantF32 {
+    op_type: Float,
+    value: f32,
+}
+
+/// A constant float value.
+#[track_caller]
+pub fn constant_f32(value: f32) -> Arc<Op> {
+    located(ConstantF32 {
+        op_type: Float,
+        value: value,
+    })
+}
+
+impl Op for ConstantF32 {
+    fn op_type(&self) -> &SpirvType {
+        &self.op_type
+    }
+
+    fn node(&self) -> Node {
+        Node::ConstantF32(self.value)
+    }
+
+    fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
+        Ok(Box::new(shader.constant_f32(self.value)?))
+    }
+}
@@ -1,11 +1,13 @@
 use super::BadOp;
 use errors::*;
+use located::located;
+use node::Node;
 use op::Op;
 use reg_op::RegOp;
 use shader::Shader;
 use spirv::Word;
 use spirv_type::SpirvType;
-use std::rc::Rc;
+use std::sync::Arc;
 use types::{Float, Vector};
 
 macro_rules! expand_vec {
@@ -14,17 +16,18 @@ macro_rules! expand_vec {
     #[derive(Debug)]
     pub struct $st {
         result_type: Vector,
-        source: Rc<Op>,
+        source: Arc<Op>,
         $($const: f32,)*
     }
 
     #[allow(missing_docs)]
-    pub fn $fn(source: Rc<Op>, $($const: f32,)*) -> Rc<Op> {
+    #[track_caller]
+    pub fn $fn(source: Arc<Op>, $($const: f32,)*) -> Arc<Op> {
         if let Some(vector) = source.op_type().as_vector() {
             if vector.component_count == $orig_size {
                 let result_type = Vector::new(Float, $dest_size);
 
-                return Rc::new($st {
+                return located($st {
                     result_type: result_type,
                     source: source,
                     $($const: $const,)*
@@ -32,7 +35,9 @@ macro_rules! expand_vec {
             }
         }
 
-        Rc::new(BadOp::new(stringify!($fn), "argument type mismatch", vec![source]))
+        let expected = format!("vec{}[float]", $orig_size);
+        let bad_op = BadOp::new(stringify!($fn), "argument type mismatch", vec![source]);
+        located(bad_op.expecting(expected))
     }
 
     impl Op for $st {
@@ -40,6 +45,10 @@ macro_rules! expand_vec {
             &self.result_type
         }
 
+        fn node(&self) -> Node {
+            Node::Expand(&self.source, vec![$(self.$const),*])
+        }
+
         fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
             let component_type = self.result_type.component.register_type(shader)?;
             let result_type = self.result_type.register_type(shader)?;
@@ -0,0 +1,98 @@
+use super::BadOp;
+use errors::*;
+use located::located;
+use node::Node;
+use op::Op;
+use reg_op::RegOp;
+use shader::Shader;
+use spirv::Word;
+use spirv_type::SpirvType;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct Extract {
+    op_type: Arc<SpirvType>,
+    composite: Arc<Op>,
+    index: u32,
+}
+
+/// Extract a single component of a vector, or a single column of a matrix.
+#[track_caller]
+pub fn extract(composite: Arc<Op>, index: u32) -> Arc<Op> {
+    let op_type = {
+        let composite_type = composite.op_type();
+
+        if let Some(vector) = composite_type.as_vector() {
+            if index < vector.component_count {
+                Some(vector.component.clone())
+            } else {
+                None
+            }
+        } else if let Some(matrix) = composite_type.as_matrix() {
+            if index < matrix.column_count {
+                Some(matrix.column_type.clone())
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    };
+
+    if let Some(op_type) = op_type {
+        return located(Extract {
+            op_type: op_type,
+            composite: composite,
+            index: index,
+        });
+    }
+
+    located(BadOp::new(
+        "extract",
+        "expected vector or matrix with enough components",
+        vec![composite],
+    ))
+}
+
+impl Op for Extract {
+    fn op_type(&self) -> &SpirvType {
+        self.op_type.as_ref()
+    }
+
+    fn node(&self) -> Node {
+        Node::Extract(&self.composite, self.index)
+    }
+
+    fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
+        let result_type = self.op_type.register_type(shader)?;
+        let composite = self.composite.register_op(shader)?;
+
+        Ok(Box::new(RegExtract {
+            result_type: result_type,
+            composite: composite,
+            index: self.index,
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct RegExtract {
+    result_type: Word,
+    composite: Box<RegOp>,
+    index: u32,
+}
+
+impl RegOp for RegExtract {
+    fn op_id(&self, shader: &mut Shader) -> Result<Option<Word>> {
+        let composite = self.composite.op_id(shader)?.ok_or(ErrorKind::NoOp)?;
+
+        let id = shader.builder.composite_extract(
+            self.result_type,
+            None,
+            composite,
+            &[self.index],
+        )?;
+
+        Ok(Some(id))
+    }
+}
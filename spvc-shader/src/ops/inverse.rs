@@ -0,0 +1,82 @@
+use super::BadOp;
+use errors::*;
+use located::located;
+use node::Node;
+use op::Op;
+use reg_op::RegOp;
+use shader::Shader;
+use spirv::Word;
+use spirv_type::SpirvType;
+use std::sync::Arc;
+use types::Matrix;
+
+/// Instruction number of MatrixInverse in GLSL.std.450.
+const MATRIX_INVERSE: u32 = 34;
+
+#[derive(Debug)]
+pub struct Inverse {
+    op_type: Matrix,
+    matrix: Arc<Op>,
+}
+
+/// Reflects the inverse of a square matrix.
+#[track_caller]
+pub fn inverse(matrix: Arc<Op>) -> Arc<Op> {
+    if let Some(op_type) = matrix.op_type().as_matrix() {
+        if op_type.column_type.row_count() == Some(op_type.column_count) {
+            return located(Inverse {
+                op_type: op_type,
+                matrix: matrix,
+            });
+        }
+    }
+
+    located(BadOp::new(
+        "inverse",
+        "expected square matrix",
+        vec![matrix],
+    ))
+}
+
+impl Op for Inverse {
+    fn op_type(&self) -> &SpirvType {
+        &self.op_type
+    }
+
+    fn node(&self) -> Node {
+        Node::Inverse(&self.matrix)
+    }
+
+    fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
+        let result_type = self.op_type.register_type(shader)?;
+        let matrix = self.matrix.register_op(shader)?;
+
+        Ok(Box::new(RegInverse {
+            result_type: result_type,
+            matrix: matrix,
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct RegInverse {
+    result_type: Word,
+    matrix: Box<RegOp>,
+}
+
+impl RegOp for RegInverse {
+    fn op_id(&self, shader: &mut Shader) -> Result<Option<Word>> {
+        let matrix = self.matrix.op_id(shader)?.ok_or(ErrorKind::NoOp)?;
+        let set = shader.glsl_std_450;
+
+        let id = shader.builder.ext_inst(
+            self.result_type,
+            None,
+            set,
+            MATRIX_INVERSE,
+            &[matrix],
+        )?;
+
+        Ok(Some(id))
+    }
+}
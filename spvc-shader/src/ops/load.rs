@@ -1,34 +1,37 @@
 use super::BadOp;
 use errors::*;
+use located::located;
+use node::Node;
 use op::Op;
 use pointer::Pointer;
 use reg_op::RegOp;
 use shader::Shader;
 use spirv::Word;
 use spirv_type::SpirvType;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct Load {
     /// Pointe type of the object being loaded.
     pub pointer: Pointer,
     /// Object being loaded.
-    pub object: Rc<Op>,
+    pub object: Arc<Op>,
 }
 
 /// Perform a load operation on the given op.
 ///
 /// Expects `object` to be a pointer.
 /// Results in an intermediate object reflecting the value of the pointee.
-pub fn load(object: Rc<Op>) -> Rc<Op> {
+#[track_caller]
+pub fn load(object: Arc<Op>) -> Arc<Op> {
     if let Some(pointer) = object.op_type().as_pointer() {
-        return Rc::new(Load {
+        return located(Load {
             pointer: pointer,
             object: object,
         });
     }
 
-    Rc::new(BadOp::new("load", "expected pointer", vec![object]))
+    located(BadOp::new("load", "expected pointer", vec![object]))
 }
 
 impl Op for Load {
@@ -36,6 +39,10 @@ impl Op for Load {
         self.pointer.pointee_type.as_ref()
     }
 
+    fn node(&self) -> Node {
+        Node::Load(&self.object)
+    }
+
     fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
         let result_type = self.pointer.pointee_type.register_type(shader)?;
         let object = self.object.register_op(shader)?;
@@ -0,0 +1,100 @@
+use super::BadOp;
+use errors::*;
+use located::located;
+use matrix_dims::MatrixDims;
+use node::Node;
+use op::Op;
+use reg_op::RegOp;
+use shader::Shader;
+use spirv::Word;
+use spirv_type::SpirvType;
+use std::sync::Arc;
+use types::{Float, Matrix, Vector};
+
+#[derive(Debug)]
+pub struct Mat4ToMat3 {
+    op_type: Matrix,
+    matrix: Arc<Op>,
+}
+
+/// Reflects the upper-left 3x3 part of a 4x4 matrix, like `mat3(m)` in GLSL.
+#[track_caller]
+pub fn mat4_to_mat3(matrix: Arc<Op>) -> Arc<Op> {
+    if let Some(dims) = matrix.op_type().as_matrix_dims() {
+        if dims == MatrixDims::new(4, 4) {
+            return located(Mat4ToMat3 {
+                op_type: Matrix::new(Vector::new(Float, 3), 3),
+                matrix: matrix,
+            });
+        }
+    }
+
+    located(BadOp::new("mat4_to_mat3", "expected mat4", vec![matrix]))
+}
+
+impl Op for Mat4ToMat3 {
+    fn op_type(&self) -> &SpirvType {
+        &self.op_type
+    }
+
+    fn node(&self) -> Node {
+        Node::Mat4ToMat3(&self.matrix)
+    }
+
+    fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
+        let column_type = self.op_type.column_type.register_type(shader)?;
+        let source_column_type = Vector::new(Float, 4).register_type(shader)?;
+        let result_type = self.op_type.register_type(shader)?;
+        let matrix = self.matrix.register_op(shader)?;
+
+        Ok(Box::new(RegMat4ToMat3 {
+            column_type: column_type,
+            source_column_type: source_column_type,
+            result_type: result_type,
+            matrix: matrix,
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct RegMat4ToMat3 {
+    column_type: Word,
+    source_column_type: Word,
+    result_type: Word,
+    matrix: Box<RegOp>,
+}
+
+impl RegOp for RegMat4ToMat3 {
+    fn op_id(&self, shader: &mut Shader) -> Result<Option<Word>> {
+        let matrix = self.matrix.op_id(shader)?.ok_or(ErrorKind::NoOp)?;
+
+        let mut columns = Vec::new();
+
+        for i in 0u32..3 {
+            let column = shader.builder.composite_extract(
+                self.source_column_type,
+                None,
+                matrix,
+                &[i],
+            )?;
+
+            let column = shader.builder.vector_shuffle(
+                self.column_type,
+                None,
+                column,
+                column,
+                &[0, 1, 2],
+            )?;
+
+            columns.push(column);
+        }
+
+        let id = shader.builder.composite_construct(
+            self.result_type,
+            None,
+            &columns,
+        )?;
+
+        Ok(Some(id))
+    }
+}
@@ -1,13 +1,28 @@
+#[macro_use]
+mod binary;
 mod load;
 mod store;
 mod mul;
 mod transpose;
 mod expand_vec;
 mod bad_op;
+mod checked;
+mod arithmetic;
+mod extract;
+mod constant;
+mod inverse;
+mod mat4_to_mat3;
 
+pub use self::arithmetic::{add, neg, sub};
 pub use self::bad_op::BadOp;
+pub use self::checked::{check, try_add, try_extract, try_inverse, try_load, try_mat4_to_mat3,
+                        try_mul, try_neg, try_store, try_sub, try_transpose};
+pub use self::constant::constant_f32;
 pub use self::expand_vec::*;
+pub use self::extract::extract;
+pub use self::inverse::inverse;
 pub use self::load::load;
+pub use self::mat4_to_mat3::mat4_to_mat3;
 pub use self::mul::mul;
 pub use self::store::store;
 pub use self::transpose::transpose;
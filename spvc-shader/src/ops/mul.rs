@@ -1,19 +1,32 @@
 use super::BadOp;
+use super::binary::float_type;
 use errors::*;
+use located::located;
+use node::Node;
 use op::Op;
 use reg_op::RegOp;
 use shader::Shader;
 use spirv::Word;
 use spirv_type::SpirvType;
-use std::rc::Rc;
+use std::sync::Arc;
 use types::{Matrix, Vector};
 
+binary_op!(ComponentMul, RegComponentMul, f_mul, Mul);
+binary_op!(VectorTimesScalarMul, RegVectorTimesScalarMul, vector_times_scalar, Mul);
+binary_op!(MatrixTimesScalarMul, RegMatrixTimesScalarMul, matrix_times_scalar, Mul);
+
 /// Perform a multiply operation on the two arguments.
 ///
 /// This operation might differ depending on the type of the arguments.
-pub fn mul(lhs: Rc<Op>, rhs: Rc<Op>) -> Rc<Op> {
+#[track_caller]
+pub fn mul(lhs: Arc<Op>, rhs: Arc<Op>) -> Arc<Op> {
+    // scalars are always multiplied from the right.
+    if lhs.op_type().as_float().is_some() && rhs.op_type().as_float().is_none() {
+        return mul(rhs, lhs);
+    }
+
     if let Some(op_type) = lhs.op_type().matrix_times_matrix(rhs.op_type()) {
-        return Rc::new(MatrixTimesMatrixMul {
+        return located(MatrixTimesMatrixMul {
             op_type: op_type,
             lhs: lhs,
             rhs: rhs,
@@ -21,14 +34,44 @@ pub fn mul(lhs: Rc<Op>, rhs: Rc<Op>) -> Rc<Op> {
     }
 
     if let Some(op_type) = lhs.op_type().matrix_times_vector(rhs.op_type()) {
-        return Rc::new(MatrixTimesVectorMul {
+        return located(MatrixTimesVectorMul {
             op_type: op_type,
             lhs: lhs,
             rhs: rhs,
         });
     }
 
-    Rc::new(BadOp::new("mul", "argument type mismatch", vec![lhs, rhs]))
+    if rhs.op_type().as_float().is_some() {
+        if let Some(matrix) = lhs.op_type().as_matrix() {
+            return located(MatrixTimesScalarMul {
+                op_type: Arc::new(matrix),
+                lhs: lhs,
+                rhs: rhs,
+            });
+        }
+
+        if let Some(op_type) = float_type(lhs.op_type()) {
+            if op_type.as_vector().is_some() {
+                return located(VectorTimesScalarMul {
+                    op_type: op_type,
+                    lhs: lhs,
+                    rhs: rhs,
+                });
+            }
+        }
+    }
+
+    if let Some(op_type) = float_type(lhs.op_type()) {
+        if op_type.matches(rhs.op_type()) {
+            return located(ComponentMul {
+                op_type: op_type,
+                lhs: lhs,
+                rhs: rhs,
+            });
+        }
+    }
+
+    located(BadOp::new("mul", "argument type mismatch", vec![lhs, rhs]))
 }
 
 #[derive(Debug)]
@@ -57,8 +100,8 @@ impl RegOp for MatrixTimesMatrixRegMul {
 #[derive(Debug)]
 pub struct MatrixTimesMatrixMul {
     op_type: Matrix,
-    lhs: Rc<Op>,
-    rhs: Rc<Op>,
+    lhs: Arc<Op>,
+    rhs: Arc<Op>,
 }
 
 impl Op for MatrixTimesMatrixMul {
@@ -66,6 +109,10 @@ impl Op for MatrixTimesMatrixMul {
         &self.op_type
     }
 
+    fn node(&self) -> Node {
+        Node::Mul(&self.lhs, &self.rhs)
+    }
+
     fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
         let result_type = self.op_type.register_type(shader)?;
 
@@ -106,8 +153,8 @@ impl RegOp for MatrixTimesVectorRegMul {
 #[derive(Debug)]
 pub struct MatrixTimesVectorMul {
     op_type: Vector,
-    lhs: Rc<Op>,
-    rhs: Rc<Op>,
+    lhs: Arc<Op>,
+    rhs: Arc<Op>,
 }
 
 impl Op for MatrixTimesVectorMul {
@@ -115,6 +162,10 @@ impl Op for MatrixTimesVectorMul {
         &self.op_type
     }
 
+    fn node(&self) -> Node {
+        Node::Mul(&self.lhs, &self.rhs)
+    }
+
     fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
         let result_type = self.op_type.register_type(shader)?;
 
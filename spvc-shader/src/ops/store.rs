@@ -1,18 +1,20 @@
 use super::BadOp;
 use errors::*;
+use located::located;
+use node::Node;
 use op::Op;
 use pointer::Pointer;
 use reg_op::RegOp;
 use shader::Shader;
 use spirv::Word;
 use spirv_type::SpirvType;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct Store {
-    dest: Rc<Op>,
+    dest: Arc<Op>,
     dest_type: Pointer,
-    source: Rc<Op>,
+    source: Arc<Op>,
 }
 
 /// Perform a store operation on the given op.
@@ -20,22 +22,26 @@ pub struct Store {
 /// Expects `dest` to be a pointer, and `source` to be an intermediate value.
 ///
 /// This operation does not have a return value.
-pub fn store(dest: Rc<Op>, source: Rc<Op>) -> Rc<Op> {
-    if let Some(dest_type) = dest.op_type().as_pointer() {
-        if dest_type.pointee_type.matches(source.op_type()) {
-            return Rc::new(Store {
-                dest: dest,
-                dest_type: dest_type,
-                source: source,
-            });
-        }
+#[track_caller]
+pub fn store(dest: Arc<Op>, source: Arc<Op>) -> Arc<Op> {
+    let dest_type = match dest.op_type().as_pointer() {
+        Some(dest_type) => dest_type,
+        None => return located(BadOp::new("store", "expected pointer", vec![dest, source])),
+    };
+
+    if dest_type.pointee_type.matches(source.op_type()) {
+        return located(Store {
+            dest: dest,
+            dest_type: dest_type,
+            source: source,
+        });
     }
 
-    Rc::new(BadOp::new(
-        "store",
-        "argument type mismatch",
-        vec![dest, source],
-    ))
+    let expected = dest_type.pointee_type.display();
+
+    located(
+        BadOp::new("store", "argument type mismatch", vec![dest, source]).expecting(expected),
+    )
 }
 
 impl Op for Store {
@@ -43,6 +49,10 @@ impl Op for Store {
         self.dest.op_type()
     }
 
+    fn node(&self) -> Node {
+        Node::Store(&self.dest, &self.source)
+    }
+
     fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
         let result_type = self.dest.op_type().register_type(shader)?;
         let dest = self.dest.register_op(shader)?;
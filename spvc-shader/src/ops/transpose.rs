@@ -1,32 +1,35 @@
 use super::BadOp;
 use errors::*;
+use located::located;
+use node::Node;
 use op::Op;
 use reg_op::RegOp;
 use shader::Shader;
 use spirv::Word;
 use spirv_type::SpirvType;
-use std::rc::Rc;
+use std::sync::Arc;
 use types::Matrix;
 
 #[derive(Debug)]
 pub struct Transpose {
     op_type: Matrix,
-    matrix: Rc<Op>,
+    matrix: Arc<Op>,
 }
 
 /// Reflects a transpose operation.
-pub fn transpose(matrix: Rc<Op>) -> Rc<Op> {
+#[track_caller]
+pub fn transpose(matrix: Arc<Op>) -> Arc<Op> {
     // Expect a matrix as argument type.
     if let Some(dims) = matrix.op_type().as_matrix_dims() {
         let op_type = dims.transpose_type();
 
-        return Rc::new(Transpose {
+        return located(Transpose {
             op_type: op_type,
             matrix: matrix,
         });
     }
 
-    Rc::new(BadOp::new(
+    located(BadOp::new(
         "transpose",
         "expected transposable matrix",
         vec![matrix],
@@ -38,6 +41,10 @@ impl Op for Transpose {
         &self.op_type
     }
 
+    fn node(&self) -> Node {
+        Node::Transpose(&self.matrix)
+    }
+
     fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
         let result_type = self.op_type.register_type(shader)?;
         let matrix = self.matrix.register_op(shader)?;
@@ -0,0 +1,519 @@
+//! # Optimization of SPIR-V modules
+//!
+//! Passes which run over a finished module, like the one produced by
+//! [`Shader::module`](../struct.Shader.html#method.module).
+
+use super::rspirv::mr::{Instruction, Module, Operand};
+use super::spirv::{self, StorageClass, Word};
+use std::collections::{HashMap, HashSet};
+
+/// Options for `optimize`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Options {
+    /// Strip all debug names, for release builds.
+    pub strip_names: bool,
+}
+
+/// Run all optimization passes over the module.
+pub fn optimize(module: &mut Module, options: Options) {
+    eliminate_common_subexpressions(module);
+    strip_unused(module);
+
+    if options.strip_names {
+        strip_names(module);
+    }
+}
+
+/// Remove all types, constants, global variables and extended instruction imports which are not
+/// used by any entry point or function, together with their names and decorations.
+pub fn strip_unused(module: &mut Module) {
+    let used = used_ids(module);
+
+    let keep = |inst: &Instruction| inst.result_id.map(|id| used.contains(&id)).unwrap_or(true);
+
+    module.types_global_values.retain(&keep);
+    module.ext_inst_imports.retain(&keep);
+
+    // names and decorations target the id in their first operand.
+    let targets_used = |inst: &Instruction| match inst.operands.get(0) {
+        Some(&Operand::IdRef(id)) => used.contains(&id),
+        _ => true,
+    };
+
+    module.debugs.retain(&targets_used);
+    module.annotations.retain(&targets_used);
+}
+
+/// Remove all `OpName` and `OpMemberName` instructions.
+pub fn strip_names(module: &mut Module) {
+    module.debugs.retain(|inst| match inst.class.opcode {
+        spirv::Op::Name | spirv::Op::MemberName => false,
+        _ => true,
+    });
+}
+
+/// Check if the instruction only depends on its operands, and has no side effects.
+///
+/// Extended instructions are only pure if they belong to one of the given sets.
+fn is_pure(inst: &Instruction, pure_sets: &[Word]) -> bool {
+    use self::spirv::Op::*;
+
+    match inst.class.opcode {
+        AccessChain | InBoundsAccessChain | FAdd | FSub | FMul | FDiv | FNegate | IAdd | ISub |
+        IMul | VectorTimesScalar | MatrixTimesScalar | VectorTimesMatrix | MatrixTimesVector |
+        MatrixTimesMatrix | Dot | CompositeExtract | CompositeConstruct | VectorShuffle |
+        Transpose | ConvertFToU | ConvertUToF | Bitcast => true,
+        ExtInst => {
+            match inst.operands.get(0) {
+                Some(&Operand::IdRef(set)) => pure_sets.contains(&set),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Merge identical pure instructions in each basic block, and loads from `Uniform` and `Input`
+/// variables which are not separated by a store to the same storage class.
+pub fn eliminate_common_subexpressions(module: &mut Module) {
+    // storage class of each pointer type.
+    let mut pointer_classes = HashMap::new();
+    // type of each id.
+    let mut types = HashMap::new();
+
+    for inst in &module.types_global_values {
+        if let (spirv::Op::TypePointer, Some(id), Some(&Operand::StorageClass(class))) =
+            (inst.class.opcode, inst.result_id, inst.operands.get(0))
+        {
+            pointer_classes.insert(id, class);
+        }
+
+        if let (Some(id), Some(ty)) = (inst.result_id, inst.result_type) {
+            types.insert(id, ty);
+        }
+    }
+
+    let pure_sets: Vec<Word> = module
+        .ext_inst_imports
+        .iter()
+        .filter(|inst| {
+            inst.operands.get(0) == Some(&Operand::LiteralString(String::from("GLSL.std.450")))
+        })
+        .filter_map(|inst| inst.result_id)
+        .collect();
+
+    for function in &mut module.functions {
+        // ids of removed instructions, and the ids replacing them.
+        let mut replaced: HashMap<Word, Word> = HashMap::new();
+
+        for block in &mut function.basic_blocks {
+            // available instructions, as opcode, result type, operands and result id.
+            let mut available: Vec<(spirv::Op, Option<Word>, Vec<Operand>, Word)> = Vec::new();
+            let mut instructions = Vec::new();
+
+            for mut inst in block.instructions.drain(..) {
+                replace_ids(&mut inst, &replaced);
+
+                if let (Some(id), Some(ty)) = (inst.result_id, inst.result_type) {
+                    types.insert(id, ty);
+                }
+
+                let opcode = inst.class.opcode;
+
+                let class = match inst.operands.get(0) {
+                    Some(&Operand::IdRef(pointer)) => {
+                        types.get(&pointer).and_then(|ty| pointer_classes.get(ty)).cloned()
+                    }
+                    _ => None,
+                };
+
+                let read_only = match class {
+                    Some(StorageClass::Uniform) |
+                    Some(StorageClass::Input) => true,
+                    _ => false,
+                };
+
+                match opcode {
+                    spirv::Op::Store if read_only => {
+                        available.retain(|a| a.0 != spirv::Op::Load);
+                    }
+                    spirv::Op::FunctionCall => {
+                        available.retain(|a| a.0 != spirv::Op::Load);
+                    }
+                    _ => {}
+                }
+
+                let mergeable = is_pure(&inst, &pure_sets) ||
+                    (opcode == spirv::Op::Load && read_only);
+
+                let id = match inst.result_id {
+                    Some(id) if mergeable => id,
+                    _ => {
+                        instructions.push(inst);
+                        continue;
+                    }
+                };
+
+                let existing = available
+                    .iter()
+                    .find(|a| a.0 == opcode && a.1 == inst.result_type && a.2 == inst.operands)
+                    .map(|a| a.3);
+
+                if let Some(existing) = existing {
+                    replaced.insert(id, existing);
+                    continue;
+                }
+
+                available.push((opcode, inst.result_type, inst.operands.clone(), id));
+                instructions.push(inst);
+            }
+
+            block.instructions = instructions;
+        }
+
+        // ids can be used in later blocks.
+        for block in &mut function.basic_blocks {
+            for inst in &mut block.instructions {
+                replace_ids(inst, &replaced);
+            }
+        }
+    }
+}
+
+/// Replace ids used as operands of the instruction.
+fn replace_ids(inst: &mut Instruction, replaced: &HashMap<Word, Word>) {
+    for operand in &mut inst.operands {
+        if let Operand::IdRef(ref mut id) = *operand {
+            if let Some(&replacement) = replaced.get(id) {
+                *id = replacement;
+            }
+        }
+    }
+}
+
+/// Ids referenced by the instruction, including its result type.
+fn references(inst: &Instruction) -> Vec<Word> {
+    let operands = inst.operands.iter().filter_map(|o| match *o {
+        Operand::IdRef(id) => Some(id),
+        _ => None,
+    });
+
+    inst.result_type.into_iter().chain(operands).collect()
+}
+
+/// Ids used by entry points and functions, directly or through the definitions of other ids.
+fn used_ids(module: &Module) -> HashSet<Word> {
+    let definitions: HashMap<Word, &Instruction> = module
+        .types_global_values
+        .iter()
+        .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+        .collect();
+
+    let mut queue = Vec::new();
+
+    for inst in module.entry_points.iter().chain(module.execution_modes.iter()) {
+        queue.extend(references(inst));
+    }
+
+    // non-semantic instructions are only referenced by each other, and are kept.
+    for inst in &module.types_global_values {
+        if inst.class.opcode == spirv::Op::ExtInst {
+            queue.extend(inst.result_id);
+        }
+    }
+
+    for function in &module.functions {
+        let body = function.basic_blocks.iter().flat_map(|b| {
+            b.label.iter().chain(b.instructions.iter())
+        });
+
+        for inst in function.def.iter().chain(function.parameters.iter()).chain(body) {
+            queue.extend(references(inst));
+        }
+    }
+
+    let mut used = HashSet::new();
+
+    while let Some(id) = queue.pop() {
+        if !used.insert(id) {
+            continue;
+        }
+
+        if let Some(inst) = definitions.get(&id) {
+            queue.extend(references(inst));
+        }
+    }
+
+    used
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eliminate_common_subexpressions, strip_names, strip_unused};
+    use rspirv::mr::{Builder, Instruction, Module, Operand};
+    use spirv::{self, Decoration, ExecutionModel, SourceLanguage, StorageClass, Word};
+
+    /// Ids of a uniform variable, holding a struct with a single float member.
+    struct Uniform {
+        float: Word,
+        pointer: Word,
+        variable: Word,
+        zero: Word,
+    }
+
+    fn uniform(b: &mut Builder, block: Decoration) -> Uniform {
+        let float = b.type_float(32);
+        let uint = b.type_int(32, 0);
+        let zero = b.constant_u32(uint, 0);
+        let st = b.type_struct(&[float]);
+        b.decorate(st, block, &[]);
+        b.member_decorate(st, 0, Decoration::Offset, &[Operand::LiteralInt32(0)]);
+        let struct_pointer = b.type_pointer(None, StorageClass::Uniform, st);
+        let pointer = b.type_pointer(None, StorageClass::Uniform, float);
+        let variable = b.variable(struct_pointer, None, StorageClass::Uniform, None);
+
+        Uniform {
+            float: float,
+            pointer: pointer,
+            variable: variable,
+            zero: zero,
+        }
+    }
+
+    /// Load the float member of the uniform.
+    fn load(b: &mut Builder, u: &Uniform) -> Word {
+        let member = b.access_chain(u.pointer, None, u.variable, &[u.zero]).unwrap();
+        b.load(u.float, None, member, None, &[]).unwrap()
+    }
+
+    /// Build a function returning void, with the given body.
+    fn function<F: FnOnce(&mut Builder)>(b: &mut Builder, body: F) -> Word {
+        let void = b.type_void();
+        let fn_type = b.type_function(void, &[]);
+        let id = b.begin_function(void, None, spirv::FUNCTION_CONTROL_NONE, fn_type)
+            .unwrap();
+        b.begin_basic_block(None).unwrap();
+        body(b);
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        id
+    }
+
+    /// Build the `main` vertex entry point, with the given body and interface.
+    fn main<F: FnOnce(&mut Builder)>(b: &mut Builder, interface: &[Word], body: F) {
+        let main = function(b, body);
+        b.entry_point(ExecutionModel::Vertex, main, String::from("main"), interface);
+    }
+
+    /// Opcodes of the instructions in the last function of the module.
+    fn opcodes(module: &Module) -> Vec<spirv::Op> {
+        let function = module.functions.last().unwrap();
+
+        function
+            .basic_blocks
+            .iter()
+            .flat_map(|b| b.instructions.iter())
+            .map(|inst| inst.class.opcode)
+            .collect()
+    }
+
+    fn instruction(module: &Module, id: Word) -> &Instruction {
+        module
+            .functions
+            .iter()
+            .flat_map(|f| f.basic_blocks.iter())
+            .flat_map(|b| b.instructions.iter())
+            .find(|inst| inst.result_id == Some(id))
+            .unwrap()
+    }
+
+    fn global_ids(module: &Module) -> Vec<Word> {
+        module
+            .ext_inst_imports
+            .iter()
+            .chain(module.types_global_values.iter())
+            .filter_map(|inst| inst.result_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_uniform_loads() {
+        let mut b = Builder::new();
+        let u = uniform(&mut b, Decoration::Block);
+        let mut first = 0;
+        let mut add = 0;
+
+        main(&mut b, &[], |b| {
+            first = load(b, &u);
+            let second = load(b, &u);
+            add = b.f_add(u.float, None, first, second).unwrap();
+        });
+
+        let mut module = b.module();
+        eliminate_common_subexpressions(&mut module);
+
+        assert_eq!(
+            opcodes(&module),
+            vec![
+                spirv::Op::AccessChain,
+                spirv::Op::Load,
+                spirv::Op::FAdd,
+                spirv::Op::Return,
+            ]
+        );
+
+        assert_eq!(
+            instruction(&module, add).operands,
+            vec![Operand::IdRef(first), Operand::IdRef(first)]
+        );
+    }
+
+    #[test]
+    fn test_store_prevents_merge() {
+        let mut b = Builder::new();
+        let u = uniform(&mut b, Decoration::BufferBlock);
+
+        main(&mut b, &[], |b| {
+            let first = load(b, &u);
+            let member = b.access_chain(u.pointer, None, u.variable, &[u.zero]).unwrap();
+            b.store(member, first, None, &[]).unwrap();
+            load(b, &u);
+        });
+
+        let mut module = b.module();
+        eliminate_common_subexpressions(&mut module);
+
+        // access chains are still merged, since they don't read memory.
+        assert_eq!(
+            opcodes(&module),
+            vec![
+                spirv::Op::AccessChain,
+                spirv::Op::Load,
+                spirv::Op::Store,
+                spirv::Op::Load,
+                spirv::Op::Return,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_function_call_prevents_merge() {
+        let mut b = Builder::new();
+        let u = uniform(&mut b, Decoration::Block);
+        let callee = function(&mut b, |_| {});
+        let void = b.type_void();
+
+        main(&mut b, &[], |b| {
+            load(b, &u);
+            b.function_call(void, None, callee, &[]).unwrap();
+            load(b, &u);
+        });
+
+        let mut module = b.module();
+        eliminate_common_subexpressions(&mut module);
+
+        assert_eq!(
+            opcodes(&module),
+            vec![
+                spirv::Op::AccessChain,
+                spirv::Op::Load,
+                spirv::Op::FunctionCall,
+                spirv::Op::Load,
+                spirv::Op::Return,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merged_ids_are_replaced_in_later_blocks() {
+        let mut b = Builder::new();
+        let u = uniform(&mut b, Decoration::Block);
+        let mut first = 0;
+        let mut add = 0;
+
+        main(&mut b, &[], |b| {
+            first = load(b, &u);
+            let second = load(b, &u);
+
+            let next = b.id();
+            b.branch(next).unwrap();
+            b.begin_basic_block(Some(next)).unwrap();
+
+            add = b.f_add(u.float, None, first, second).unwrap();
+        });
+
+        let mut module = b.module();
+        eliminate_common_subexpressions(&mut module);
+
+        assert_eq!(
+            instruction(&module, add).operands,
+            vec![Operand::IdRef(first), Operand::IdRef(first)]
+        );
+    }
+
+    #[test]
+    fn test_strip_unused() {
+        let mut b = Builder::new();
+        let glsl_std_450 = b.ext_inst_import(String::from("GLSL.std.450"));
+
+        let float = b.type_float(32);
+        let one = b.constant_f32(float, 1.0);
+        let output_pointer = b.type_pointer(None, StorageClass::Output, float);
+        let output = b.variable(output_pointer, None, StorageClass::Output, None);
+        b.name(output, String::from("output"));
+        b.decorate(output, Decoration::Location, &[Operand::LiteralInt32(0)]);
+
+        let uint = b.type_int(32, 0);
+        let vector = b.type_vector(float, 4);
+        let two = b.constant_u32(uint, 2);
+        let private_pointer = b.type_pointer(None, StorageClass::Private, float);
+        let private = b.variable(private_pointer, None, StorageClass::Private, None);
+        b.name(private, String::from("private"));
+        b.decorate(private, Decoration::RelaxedPrecision, &[]);
+
+        main(&mut b, &[output], |b| { b.store(output, one, None, &[]).unwrap(); });
+
+        let mut module = b.module();
+        let unused = vec![glsl_std_450, uint, vector, two, private_pointer, private];
+
+        let before = global_ids(&module);
+        assert!(unused.iter().all(|id| before.contains(id)));
+
+        strip_unused(&mut module);
+
+        let after = global_ids(&module);
+        assert!(unused.iter().all(|id| !after.contains(id)));
+
+        for id in &[float, one, output_pointer, output] {
+            assert!(after.contains(id));
+        }
+
+        let targets = |insts: &[Instruction]| -> Vec<Operand> {
+            insts.iter().map(|inst| inst.operands[0].clone()).collect()
+        };
+
+        assert_eq!(targets(&module.debugs), vec![Operand::IdRef(output)]);
+        assert_eq!(targets(&module.annotations), vec![Operand::IdRef(output)]);
+    }
+
+    #[test]
+    fn test_strip_names() {
+        let mut b = Builder::new();
+        let file = b.string(String::from("shader.rs"));
+        b.source(SourceLanguage::Unknown, 0, Some(file), None);
+
+        let float = b.type_float(32);
+        let st = b.type_struct(&[float]);
+        b.name(st, String::from("Global"));
+        b.member_name(st, 0, String::from("value"));
+
+        main(&mut b, &[], |b| b.line(file, 1, 1));
+
+        let mut module = b.module();
+        strip_names(&mut module);
+
+        let debugs: Vec<spirv::Op> = module.debugs.iter().map(|inst| inst.class.opcode).collect();
+        assert_eq!(debugs, vec![spirv::Op::String, spirv::Op::Source]);
+        assert!(opcodes(&module).contains(&spirv::Op::Line));
+    }
+}
@@ -1,5 +1,7 @@
+use super::decorations::{Interpolation, Sampling, VarDecorations};
 use super::errors::*;
 use super::interface::Interface;
+use super::node::Node;
 use super::op::Op;
 use super::op_key::OpKey;
 use super::pointer::Pointer;
@@ -8,7 +10,7 @@ use super::rspirv::mr::Operand;
 use super::shader::Shader;
 use super::spirv::{Decoration, StorageClass};
 use super::spirv_type::{SpirvType, WrapperType};
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Reflection of an output variable.
 #[derive(Debug)]
@@ -17,8 +19,12 @@ pub struct OutputVar {
     pub name: String,
     /// Type of the output variable, packed into a pointer.
     pub pointer: Pointer,
-    /// Location of the output variable.
-    pub location: u32,
+    /// Explicit location of the output variable.
+    ///
+    /// If `None`, a location is assigned when the variable is used in an entry point.
+    pub location: Option<u32>,
+    /// Decorations of the output variable, in addition to its location.
+    pub decorations: VarDecorations,
 }
 
 impl WrapperType for OutputVar {
@@ -40,13 +46,20 @@ impl Op for OutputVar {
         &self.pointer
     }
 
+    fn node(&self) -> Node {
+        Node::Output(self)
+    }
+
     fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
         let variable_type = self.pointer.register_type(shader)?;
+        let location = shader.locations.resolve(Interface::Output(self))?;
 
         let id = shader.cache_op(
             OpKey::OutputVar {
                 variable_type: variable_type,
-                location: self.location,
+                location: location,
+                name: self.name.clone(),
+                decorations: self.decorations.clone(),
             },
             |s| {
                 let variable_id = s.builder.variable(
@@ -61,9 +74,11 @@ impl Op for OutputVar {
                 s.builder.decorate(
                     variable_id,
                     Decoration::Location,
-                    &[Operand::LiteralInt32(self.location)],
+                    &[Operand::LiteralInt32(location)],
                 );
 
+                self.decorations.register(variable_id, s);
+
                 Ok(variable_id)
             },
         )?;
@@ -74,11 +89,29 @@ impl Op for OutputVar {
 
 impl OutputVar {
     /// Create a new output variable.
-    pub fn new<T: 'static + SpirvType>(name: &str, ty: T, location: u32) -> Rc<OutputVar> {
-        Rc::new(OutputVar {
+    pub fn new<T: 'static + SpirvType>(name: &str, ty: T, location: u32) -> Arc<OutputVar> {
+        Arc::new(OutputVar {
+            name: String::from(name),
+            pointer: Pointer::new(StorageClass::Output, Arc::new(ty)),
+            location: Some(location),
+            decorations: VarDecorations::default(),
+        })
+    }
+
+    /// Create a builder for an output variable with additional decorations.
+    pub fn builder<T: 'static + SpirvType>(name: &str, ty: T) -> OutputVarBuilder {
+        OutputVarBuilder::new(name, Arc::new(ty))
+    }
+
+    /// Create a new output variable, without an explicit location.
+    ///
+    /// Requires the shader to use `LocationMode::Auto`.
+    pub fn auto<T: 'static + SpirvType>(name: &str, ty: T) -> Arc<OutputVar> {
+        Arc::new(OutputVar {
             name: String::from(name),
-            pointer: Pointer::new(StorageClass::Output, Rc::new(ty)),
-            location: location,
+            pointer: Pointer::new(StorageClass::Output, Arc::new(ty)),
+            location: None,
+            decorations: VarDecorations::default(),
         })
     }
 
@@ -102,3 +135,84 @@ impl OutputVar {
         })
     }
 }
+
+/// Builder of output variables.
+#[derive(Debug)]
+pub struct OutputVarBuilder {
+    name: String,
+    ty: Arc<SpirvType>,
+    location: Option<u32>,
+    decorations: VarDecorations,
+}
+
+impl OutputVarBuilder {
+    /// Create a new builder, with an already shared type.
+    pub(crate) fn new(name: &str, ty: Arc<SpirvType>) -> OutputVarBuilder {
+        OutputVarBuilder {
+            name: String::from(name),
+            ty: ty,
+            location: None,
+            decorations: VarDecorations::default(),
+        }
+    }
+
+    /// Set the location of the variable.
+    ///
+    /// If not set, the shader must use `LocationMode::Auto`.
+    pub fn location(mut self, location: u32) -> OutputVarBuilder {
+        self.location = Some(location);
+        self
+    }
+
+    /// Use flat interpolation, required for integer variables passed to fragment shaders.
+    pub fn flat(mut self) -> OutputVarBuilder {
+        self.decorations.interpolation = Interpolation::Flat;
+        self
+    }
+
+    /// Use linear interpolation in screen space.
+    pub fn no_perspective(mut self) -> OutputVarBuilder {
+        self.decorations.interpolation = Interpolation::NoPerspective;
+        self
+    }
+
+    /// Sample the variable within the covered area of the pixel.
+    pub fn centroid(mut self) -> OutputVarBuilder {
+        self.decorations.sampling = Sampling::Centroid;
+        self
+    }
+
+    /// Sample the variable once per sample.
+    pub fn sample(mut self) -> OutputVarBuilder {
+        self.decorations.sampling = Sampling::Sample;
+        self
+    }
+
+    /// Set the first component of the location occupied by the variable.
+    pub fn component(mut self, component: u32) -> OutputVarBuilder {
+        self.decorations.component = Some(component);
+        self
+    }
+
+    /// Set the blend equation index of the variable, for fragment outputs.
+    pub fn index(mut self, index: u32) -> OutputVarBuilder {
+        self.decorations.index = Some(index);
+        self
+    }
+
+    /// Decorate the variable as invariant.
+    pub fn invariant(mut self) -> OutputVarBuilder {
+        self.decorations.invariant = true;
+        self
+    }
+
+    /// Build the output variable.
+    pub fn build(self) -> Arc<OutputVar> {
+        Arc::new(OutputVar {
+            name: self.name,
+            pointer: Pointer::new(StorageClass::Output, self.ty),
+            location: self.location,
+            decorations: self.decorations,
+        })
+    }
+}
@@ -2,21 +2,26 @@ use super::errors::*;
 use super::shader::Shader;
 use super::spirv::{StorageClass, Word};
 use super::spirv_type::SpirvType;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct Pointer {
     storage_class: StorageClass,
-    pub pointee_type: Rc<SpirvType>,
+    pub pointee_type: Arc<SpirvType>,
 }
 
 impl Pointer {
-    pub fn new(storage_class: StorageClass, pointee_type: Rc<SpirvType>) -> Pointer {
+    pub fn new(storage_class: StorageClass, pointee_type: Arc<SpirvType>) -> Pointer {
         Pointer {
             storage_class: storage_class,
             pointee_type: pointee_type,
         }
     }
+
+    /// Storage class of the pointer.
+    pub fn storage_class(&self) -> StorageClass {
+        self.storage_class
+    }
 }
 
 impl SpirvType for Pointer {
@@ -39,12 +44,8 @@ impl SpirvType for Pointer {
         4
     }
 
-    fn matches(&self, other: &SpirvType) -> bool {
-        if let Some(pointer) = other.as_pointer() {
-            return self.pointee_type.matches(pointer.pointee_type.as_ref());
-        }
-
-        false
+    fn location_slots(&self) -> u32 {
+        self.pointee_type.location_slots()
     }
 
     fn display(&self) -> String {
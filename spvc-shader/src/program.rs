@@ -0,0 +1,246 @@
+//! # Programs combining multiple shader stages
+//!
+//! A program validates that the outputs of one stage line up with the inputs of the next.
+
+use super::decorations::{Interpolation, VarDecorations};
+use super::errors::*;
+use super::input_var::InputVar;
+use super::interface::Interface;
+use super::shader::{EntryPoint, Shader, ShaderKind};
+use super::spirv::StorageClass;
+use super::spirv_type::SpirvType;
+use std::sync::Arc;
+
+/// An input or output variable of a stage, with its location resolved.
+struct StageVar<'a> {
+    name: &'a str,
+    location: u32,
+    ty: &'a Arc<SpirvType>,
+    decorations: &'a VarDecorations,
+}
+
+/// A collection of shaders, making up the stages of a graphics pipeline.
+#[derive(Debug)]
+pub struct Program {
+    shaders: Vec<Shader>,
+}
+
+impl Program {
+    /// Create a new, empty program.
+    pub fn new() -> Program {
+        Program { shaders: Vec::new() }
+    }
+
+    /// Add a shader to the program.
+    pub fn shader(&mut self, shader: Shader) {
+        self.shaders.push(shader);
+    }
+
+    /// Access the shaders in the program.
+    pub fn shaders(&self) -> &[Shader] {
+        &self.shaders
+    }
+
+    /// Convert the program into its shaders.
+    pub fn into_shaders(self) -> Vec<Shader> {
+        self.shaders
+    }
+
+    /// Validate that every input of the fragment stage is provided by a matching output of the
+    /// vertex stage.
+    ///
+    /// Matching outputs must have the same location, component type, component count and
+    /// interpolation decorations. With several vertex or fragment entry points, every combination
+    /// of them is validated.
+    pub fn validate(&self) -> Result<()> {
+        for vertex in self.stages(ShaderKind::Vertex) {
+            let outputs = stage_vars(vertex.0, vertex.1, StorageClass::Output)?;
+
+            for fragment in self.stages(ShaderKind::Fragment) {
+                let inputs = stage_vars(fragment.0, fragment.1, StorageClass::Input)?;
+                check_interfaces(&outputs, &inputs)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Generate fragment inputs matching every output of the vertex stage.
+    ///
+    /// The generated inputs have the same name, type, location and interpolation decorations as
+    /// the corresponding output, except that integer inputs are always flat. With several vertex
+    /// entry points, outputs at the same location must match.
+    pub fn fragment_inputs(&self) -> Result<Vec<Arc<InputVar>>> {
+        let vertex = self.stages(ShaderKind::Vertex);
+
+        if vertex.is_empty() {
+            return Err(ErrorKind::MissingStage(ShaderKind::Vertex).into());
+        }
+
+        let mut outputs: Vec<StageVar> = Vec::new();
+        let mut out = Vec::new();
+
+        for (shader, entry_point) in vertex {
+            for var in stage_vars(shader, entry_point, StorageClass::Output)? {
+                if let Some(existing) = outputs.iter().find(|o| o.location == var.location) {
+                    if let Some(reason) = mismatch(existing, &var) {
+                        return Err(
+                            ErrorKind::StageInterfaceMismatch(
+                                existing.name.to_string(),
+                                var.name.to_string(),
+                                reason,
+                            ).into(),
+                        );
+                    }
+
+                    continue;
+                }
+
+                let interpolation = if is_integer(var.ty.as_ref()) {
+                    Interpolation::Flat
+                } else {
+                    var.decorations.interpolation
+                };
+
+                let decorations = VarDecorations {
+                    interpolation: interpolation,
+                    sampling: var.decorations.sampling,
+                    component: var.decorations.component,
+                    ..VarDecorations::default()
+                };
+
+                out.push(InputVar::from_type(
+                    var.name,
+                    var.ty.clone(),
+                    Some(var.location),
+                    decorations,
+                ));
+
+                outputs.push(var);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Find all entry points of the given kind.
+    fn stages(&self, kind: ShaderKind) -> Vec<(&Shader, &EntryPoint)> {
+        let mut out = Vec::new();
+
+        for shader in &self.shaders {
+            for entry_point in &shader.entry_points {
+                if entry_point.kind == kind {
+                    out.push((shader, entry_point));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Collect the variables with the given storage class from the interface of an entry point.
+fn stage_vars<'a>(
+    shader: &'a Shader,
+    entry_point: &'a EntryPoint,
+    storage_class: StorageClass,
+) -> Result<Vec<StageVar<'a>>> {
+    use self::Interface::*;
+
+    let mut out = Vec::new();
+
+    for op in &entry_point.interface {
+        let (var, ty, decorations) = match op.as_interface() {
+            Some(Input(var)) if storage_class == StorageClass::Input => {
+                (Input(var), &var.pointer.pointee_type, &var.decorations)
+            }
+            Some(Output(var)) if storage_class == StorageClass::Output => {
+                (Output(var), &var.pointer.pointee_type, &var.decorations)
+            }
+            _ => continue,
+        };
+
+        out.push(StageVar {
+            name: var.name(),
+            location: shader.locations.resolve(var)?,
+            ty: ty,
+            decorations: decorations,
+        });
+    }
+
+    Ok(out)
+}
+
+/// Check that every input is matched by an output.
+fn check_interfaces(outputs: &[StageVar], inputs: &[StageVar]) -> Result<()> {
+    for input in inputs {
+        let output = match outputs.iter().find(|o| o.location == input.location) {
+            Some(output) => output,
+            None => {
+                return Err(
+                    ErrorKind::UnmatchedStageInput(input.name.to_string(), input.location).into(),
+                )
+            }
+        };
+
+        if let Some(reason) = mismatch(output, input) {
+            return Err(
+                ErrorKind::StageInterfaceMismatch(
+                    output.name.to_string(),
+                    input.name.to_string(),
+                    reason,
+                ).into(),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Describe how an output and an input at the same location differ, if at all.
+fn mismatch(output: &StageVar, input: &StageVar) -> Option<&'static str> {
+    // integer inputs are flat, whatever the output is decorated with.
+    let integer = is_integer(input.ty.as_ref());
+
+    if !integer && output.decorations.interpolation != input.decorations.interpolation {
+        return Some("interpolation differs");
+    }
+
+    if output.decorations.sampling != input.decorations.sampling {
+        return Some("sampling differs");
+    }
+
+    if output.decorations.component != input.decorations.component {
+        return Some("component decoration differs");
+    }
+
+    if output.ty.location_slots() != input.ty.location_slots() {
+        return Some("location count differs");
+    }
+
+    if let (Some(a), Some(b)) = (output.ty.as_vector(), input.ty.as_vector()) {
+        if !a.component.matches(b.component.as_ref()) {
+            return Some("component type differs");
+        }
+
+        if a.component_count != b.component_count {
+            return Some("component count differs");
+        }
+
+        return None;
+    }
+
+    if !output.ty.matches(input.ty.as_ref()) {
+        return Some("type differs");
+    }
+
+    None
+}
+
+/// Check if the given type is an integer, or a vector of integers.
+fn is_integer(ty: &SpirvType) -> bool {
+    match ty.as_vector() {
+        Some(vector) => vector.component.as_unsigned_integer().is_some(),
+        None => ty.as_unsigned_integer().is_some(),
+    }
+}
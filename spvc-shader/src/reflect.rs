@@ -0,0 +1,908 @@
+//! # Reflection of entry points
+//!
+//! A description of the interface of each entry point which doesn't depend on any particular
+//! graphics API. With the `serialize` feature, it can be serialized using serde.
+//!
+//! Entry points are reflected either when they are added to a [`Shader`](../struct.Shader.html),
+//! or from an existing SPIR-V module using [`from_module`](fn.from_module.html).
+
+use super::backend::walk;
+use super::decorations::{Interpolation, Sampling};
+use super::errors::*;
+use super::function::Function;
+use super::interface::Interface;
+use super::locations::Locations;
+use super::node::Node;
+use super::op::Op;
+use super::rspirv::mr::{Module, Operand};
+use super::shader::ShaderKind;
+use super::spirv::{self, Decoration, ExecutionModel, StorageClass, Word};
+use super::spirv_type::SpirvType;
+use super::uniform_var::UniformVar;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Reflection of a single entry point.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct EntryPointInfo {
+    /// Name of the entry point.
+    pub name: String,
+    /// Stage of the entry point.
+    pub kind: ShaderKind,
+    /// Input variables, ordered by location.
+    pub inputs: Vec<InterfaceVarInfo>,
+    /// Output variables, ordered by location.
+    pub outputs: Vec<InterfaceVarInfo>,
+    /// Descriptor sets, ordered by set.
+    pub descriptor_sets: Vec<DescriptorSetInfo>,
+    /// Push constant blocks.
+    pub push_constants: Vec<PushConstantInfo>,
+    /// Specialization constants.
+    pub spec_constants: Vec<SpecConstantInfo>,
+}
+
+/// An input or output variable.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct InterfaceVarInfo {
+    /// Name of the variable.
+    pub name: String,
+    /// First location of the variable.
+    pub location: u32,
+    /// Number of locations occupied by the variable.
+    pub location_count: u32,
+    /// First component of the location the variable occupies, if not the first.
+    pub component: Option<u32>,
+    /// If the variable is interpolated without perspective correction, or not at all.
+    pub interpolation: Interpolation,
+    /// Where the variable is sampled.
+    pub sampling: Sampling,
+    /// Type of the variable.
+    pub ty: TypeInfo,
+}
+
+/// A descriptor set, and its bindings.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct DescriptorSetInfo {
+    /// Index of the set.
+    pub set: u32,
+    /// Bindings in the set, ordered by binding.
+    pub bindings: Vec<DescriptorBindingInfo>,
+}
+
+/// Kind of resource bound to a descriptor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum DescriptorKind {
+    /// A uniform buffer.
+    UniformBuffer,
+    /// A storage buffer.
+    StorageBuffer,
+    /// A sampler.
+    Sampler,
+    /// An image combined with a sampler.
+    CombinedImageSampler,
+    /// An image which is sampled.
+    SampledImage,
+    /// An image which is read and written without a sampler.
+    StorageImage,
+}
+
+/// A single binding in a descriptor set.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct DescriptorBindingInfo {
+    /// Index of the binding.
+    pub binding: u32,
+    /// Name of the variable bound.
+    pub name: String,
+    /// Kind of the descriptor.
+    pub kind: DescriptorKind,
+    /// Type of the bound resource.
+    pub ty: TypeInfo,
+}
+
+/// A push constant block.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct PushConstantInfo {
+    /// Name of the variable.
+    pub name: String,
+    /// Offset of the block.
+    pub offset: u32,
+    /// Size of the block.
+    pub size: u32,
+    /// Type of the block.
+    pub ty: TypeInfo,
+}
+
+/// A specialization constant.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct SpecConstantInfo {
+    /// Name of the constant.
+    pub name: String,
+    /// Constant id used when specializing.
+    pub id: u32,
+    /// Type of the constant.
+    pub ty: TypeInfo,
+}
+
+/// A reflected type.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum TypeInfo {
+    /// A boolean.
+    Bool,
+    /// A 32-bit float.
+    Float,
+    /// A 32-bit unsigned integer.
+    UnsignedInteger,
+    /// A vector.
+    Vector {
+        /// Component type.
+        component: Box<TypeInfo>,
+        /// Number of components.
+        count: u32,
+    },
+    /// A matrix, stored by columns.
+    Matrix {
+        /// Column type.
+        column: Box<TypeInfo>,
+        /// Number of columns.
+        columns: u32,
+    },
+    /// A struct, with its layout.
+    Struct(StructLayout),
+    /// A type which has no equivalent here, such as an array or an image, named after the
+    /// instruction declaring it. Its size is unknown.
+    Opaque(String),
+}
+
+/// Layout of a struct.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct StructLayout {
+    /// Name of the struct.
+    pub name: String,
+    /// Size of the struct in bytes.
+    pub size: u32,
+    /// Members of the struct.
+    pub members: Vec<MemberLayout>,
+}
+
+/// Layout of a struct member.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct MemberLayout {
+    /// Name of the member.
+    pub name: String,
+    /// Offset of the member in bytes.
+    pub offset: u32,
+    /// Type of the member.
+    pub ty: TypeInfo,
+}
+
+impl TypeInfo {
+    /// Reflect the given type.
+    pub fn from_type(ty: &SpirvType) -> Result<TypeInfo> {
+        if ty.as_bool().is_some() {
+            return Ok(TypeInfo::Bool);
+        }
+
+        if ty.as_float().is_some() {
+            return Ok(TypeInfo::Float);
+        }
+
+        if ty.as_unsigned_integer().is_some() {
+            return Ok(TypeInfo::UnsignedInteger);
+        }
+
+        if let Some(vector) = ty.as_vector() {
+            return Ok(TypeInfo::Vector {
+                component: Box::new(TypeInfo::from_type(vector.component.as_ref())?),
+                count: vector.component_count,
+            });
+        }
+
+        if let Some(matrix) = ty.as_matrix() {
+            return Ok(TypeInfo::Matrix {
+                column: Box::new(TypeInfo::from_type(matrix.column_type.as_ref())?),
+                columns: matrix.column_count,
+            });
+        }
+
+        if let Some(st) = ty.as_struct() {
+            let mut members = Vec::new();
+            // members are tightly packed, the same as when the struct is registered.
+            let mut offset = 0u32;
+
+            for m in &st.members {
+                members.push(MemberLayout {
+                    name: m.name.to_string(),
+                    offset: offset,
+                    ty: TypeInfo::from_type(m.ty.as_ref())?,
+                });
+
+                offset += m.ty.width();
+            }
+
+            return Ok(TypeInfo::Struct(StructLayout {
+                name: st.name.to_string(),
+                size: offset,
+                members: members,
+            }));
+        }
+
+        Err(ErrorKind::IllegalInterfaceType.into())
+    }
+
+    /// Number of locations occupied by an interface variable of this type.
+    pub fn location_slots(&self) -> u32 {
+        match *self {
+            TypeInfo::Matrix { ref column, columns } => column.location_slots() * columns,
+            _ => 1,
+        }
+    }
+}
+
+/// Add a binding to the given descriptor sets, unless it is already present.
+fn add_binding(
+    descriptor_sets: &mut Vec<DescriptorSetInfo>,
+    set: u32,
+    binding: DescriptorBindingInfo,
+) {
+    if let Some(s) = descriptor_sets.iter_mut().find(|s| s.set == set) {
+        if !s.bindings.iter().any(|b| b.binding == binding.binding) {
+            s.bindings.push(binding);
+        }
+
+        return;
+    }
+
+    descriptor_sets.push(DescriptorSetInfo {
+        set: set,
+        bindings: vec![binding],
+    });
+}
+
+/// Reflect an entry point with the given interface.
+///
+/// Uniform variables are included if they are used by the function, whether or not they are part
+/// of the interface.
+pub(crate) fn entry_point_info(
+    name: &str,
+    kind: ShaderKind,
+    function: &Function,
+    interface: &[Arc<Op>],
+    locations: &Locations,
+) -> Result<EntryPointInfo> {
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    let mut descriptor_sets: Vec<DescriptorSetInfo> = Vec::new();
+
+    for op in interface {
+        let interface = op.as_interface().ok_or(ErrorKind::NotInterface)?;
+
+        let (dest, location, var_name, ty, decorations) = match interface {
+            Interface::Input(var) => (
+                &mut inputs,
+                locations.resolve(Interface::Input(var))?,
+                &var.name,
+                &var.pointer.pointee_type,
+                &var.decorations,
+            ),
+            Interface::Output(var) => (
+                &mut outputs,
+                locations.resolve(Interface::Output(var))?,
+                &var.name,
+                &var.pointer.pointee_type,
+                &var.decorations,
+            ),
+            // uniforms are only reflected if the function uses them.
+            Interface::Uniform(_) | Interface::BuiltIn(_) => continue,
+        };
+
+        let ty = TypeInfo::from_type(ty.as_ref())?;
+
+        dest.push(InterfaceVarInfo {
+            name: var_name.clone(),
+            location: location,
+            location_count: ty.location_slots(),
+            component: decorations.component,
+            interpolation: decorations.interpolation,
+            sampling: decorations.sampling,
+            ty: ty,
+        });
+    }
+
+    let mut used = Vec::new();
+
+    walk(function.ops(), |op| if let Node::Uniform(var) = op.node() {
+        used.push(var);
+    });
+
+    for var in used {
+        add_binding(&mut descriptor_sets, var.set, uniform_binding(var)?);
+    }
+
+    inputs.sort_by_key(|v| v.location);
+    outputs.sort_by_key(|v| v.location);
+    descriptor_sets.sort_by_key(|s| s.set);
+
+    for set in &mut descriptor_sets {
+        set.bindings.sort_by_key(|b| b.binding);
+    }
+
+    Ok(EntryPointInfo {
+        name: name.to_string(),
+        kind: kind,
+        inputs: inputs,
+        outputs: outputs,
+        descriptor_sets: descriptor_sets,
+        push_constants: Vec::new(),
+        spec_constants: Vec::new(),
+    })
+}
+
+fn uniform_binding(var: &UniformVar) -> Result<DescriptorBindingInfo> {
+    Ok(DescriptorBindingInfo {
+        binding: var.binding,
+        name: var.name.clone(),
+        kind: if var.is_storage_buffer() {
+            DescriptorKind::StorageBuffer
+        } else {
+            DescriptorKind::UniformBuffer
+        },
+        ty: TypeInfo::from_type(var.pointer.pointee_type.as_ref())?,
+    })
+}
+
+/// Decorations and names of ids in a module.
+#[derive(Default)]
+struct Annotations {
+    names: HashMap<Word, String>,
+    member_names: HashMap<(Word, u32), String>,
+    decorations: HashMap<Word, Vec<(Decoration, Option<u32>)>>,
+    member_offsets: HashMap<(Word, u32), u32>,
+}
+
+impl Annotations {
+    fn new(module: &Module) -> Annotations {
+        let mut a = Annotations::default();
+
+        for inst in &module.debugs {
+            let o = &inst.operands;
+
+            match (inst.class.opcode, o.get(0), o.get(1), o.get(2)) {
+                (spirv::Op::Name,
+                 Some(&Operand::IdRef(id)),
+                 Some(&Operand::LiteralString(ref name)),
+                 _) => {
+                    a.names.insert(id, name.clone());
+                }
+                (spirv::Op::MemberName,
+                 Some(&Operand::IdRef(id)),
+                 Some(&Operand::LiteralInt32(member)),
+                 Some(&Operand::LiteralString(ref name))) => {
+                    a.member_names.insert((id, member), name.clone());
+                }
+                _ => {}
+            }
+        }
+
+        for inst in &module.annotations {
+            let o = &inst.operands;
+
+            match (inst.class.opcode, o.get(0), o.get(1), o.get(2), o.get(3)) {
+                (spirv::Op::Decorate,
+                 Some(&Operand::IdRef(id)),
+                 Some(&Operand::Decoration(decoration)),
+                 value,
+                 _) => {
+                    let value = match value {
+                        Some(&Operand::LiteralInt32(value)) => Some(value),
+                        _ => None,
+                    };
+
+                    a.decorations.entry(id).or_insert_with(Vec::new).push((decoration, value));
+                }
+                (spirv::Op::MemberDecorate,
+                 Some(&Operand::IdRef(id)),
+                 Some(&Operand::LiteralInt32(member)),
+                 Some(&Operand::Decoration(Decoration::Offset)),
+                 Some(&Operand::LiteralInt32(offset))) => {
+                    a.member_offsets.insert((id, member), offset);
+                }
+                _ => {}
+            }
+        }
+
+        a
+    }
+
+    fn name(&self, id: Word) -> String {
+        self.names.get(&id).cloned().unwrap_or_else(|| format!("%{}", id))
+    }
+
+    fn has(&self, id: Word, decoration: Decoration) -> bool {
+        self.get(id, decoration).is_some()
+    }
+
+    /// Look up a decoration, and its value if it has one.
+    fn get(&self, id: Word, decoration: Decoration) -> Option<Option<u32>> {
+        self.decorations.get(&id).and_then(|d| {
+            d.iter().find(|d| d.0 == decoration).map(|d| d.1)
+        })
+    }
+}
+
+/// Size of a reflected type in bytes.
+fn type_size(ty: &TypeInfo) -> u32 {
+    match *ty {
+        TypeInfo::Bool | TypeInfo::Float | TypeInfo::UnsignedInteger => 4,
+        TypeInfo::Vector { ref component, count } => type_size(component) * count,
+        TypeInfo::Matrix { ref column, columns } => type_size(column) * columns,
+        TypeInfo::Struct(ref st) => st.size,
+        TypeInfo::Opaque(_) => 0,
+    }
+}
+
+/// Type with the given id, or an opaque type if it isn't known.
+fn type_of(types: &HashMap<Word, TypeInfo>, id: Word) -> TypeInfo {
+    types.get(&id).cloned().unwrap_or_else(|| TypeInfo::Opaque(format!("%{}", id)))
+}
+
+/// Reflect all types in the module. Types which have no equivalent `TypeInfo` are opaque.
+fn module_types(module: &Module, a: &Annotations) -> HashMap<Word, TypeInfo> {
+    let mut types: HashMap<Word, TypeInfo> = HashMap::new();
+
+    for inst in &module.types_global_values {
+        let id = match inst.result_id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let ty = match (inst.class.opcode, &inst.operands[..]) {
+            (spirv::Op::TypeBool, _) => TypeInfo::Bool,
+            (spirv::Op::TypeFloat, &[Operand::LiteralInt32(32)]) => TypeInfo::Float,
+            (spirv::Op::TypeInt, &[Operand::LiteralInt32(32), Operand::LiteralInt32(0)]) => {
+                TypeInfo::UnsignedInteger
+            }
+            (spirv::Op::TypeVector, &[Operand::IdRef(component), Operand::LiteralInt32(count)]) => {
+                TypeInfo::Vector {
+                    component: Box::new(type_of(&types, component)),
+                    count: count,
+                }
+            }
+            (spirv::Op::TypeMatrix, &[Operand::IdRef(column), Operand::LiteralInt32(columns)]) => {
+                TypeInfo::Matrix {
+                    column: Box::new(type_of(&types, column)),
+                    columns: columns,
+                }
+            }
+            (spirv::Op::TypeStruct, operands) => {
+                TypeInfo::Struct(struct_layout(id, operands, &types, a))
+            }
+            (opcode @ spirv::Op::TypeInt, _) |
+            (opcode @ spirv::Op::TypeFloat, _) |
+            (opcode @ spirv::Op::TypeArray, _) |
+            (opcode @ spirv::Op::TypeRuntimeArray, _) |
+            (opcode @ spirv::Op::TypeImage, _) |
+            (opcode @ spirv::Op::TypeSampler, _) |
+            (opcode @ spirv::Op::TypeSampledImage, _) => TypeInfo::Opaque(format!("{:?}", opcode)),
+            _ => continue,
+        };
+
+        types.insert(id, ty);
+    }
+
+    types
+}
+
+fn struct_layout(
+    id: Word,
+    operands: &[Operand],
+    types: &HashMap<Word, TypeInfo>,
+    a: &Annotations,
+) -> StructLayout {
+    let mut members = Vec::new();
+    let mut size = 0u32;
+
+    for (index, operand) in operands.iter().enumerate() {
+        let index = index as u32;
+
+        let ty = match *operand {
+            Operand::IdRef(member_type) => type_of(types, member_type),
+            _ => continue,
+        };
+
+        // structs which are not blocks have no explicit offsets, and are tightly packed.
+        let offset = a.member_offsets.get(&(id, index)).cloned().unwrap_or(size);
+        size = ::std::cmp::max(size, offset + type_size(&ty));
+
+        members.push(MemberLayout {
+            name: a.member_names.get(&(id, index)).cloned().unwrap_or_else(
+                || index.to_string(),
+            ),
+            offset: offset,
+            ty: ty,
+        });
+    }
+
+    StructLayout {
+        name: a.name(id),
+        size: size,
+        members: members,
+    }
+}
+
+/// Reflect all entry points of an existing module.
+///
+/// Since SPIR-V 1.0 only lists inputs and outputs in the interface of an entry point, uniform
+/// variables and push constants are included in an entry point if its function, or any function
+/// it calls, uses them. All specialization constants are included in each entry point. Types
+/// which have no equivalent `TypeInfo` are reflected as opaque.
+pub fn from_module(module: &Module) -> Result<Vec<EntryPointInfo>> {
+    let a = Annotations::new(module);
+    let types = module_types(module, &a);
+
+    let lookup = |id: Word| type_of(&types, id);
+
+    // pointer types, by storage class and pointee.
+    let mut pointers = HashMap::new();
+    let mut globals = Globals::default();
+    let mut spec_constants = Vec::new();
+
+    for inst in &module.types_global_values {
+        let o = &inst.operands;
+
+        match (inst.class.opcode, o.get(0), o.get(1), inst.result_id) {
+            (spirv::Op::TypePointer,
+             Some(&Operand::StorageClass(storage_class)),
+             Some(&Operand::IdRef(pointee)),
+             Some(id)) => {
+                pointers.insert(id, (storage_class, pointee));
+            }
+            (spirv::Op::TypeSampler, _, _, Some(id)) => {
+                globals.image_kinds.insert(id, DescriptorKind::Sampler);
+            }
+            (spirv::Op::TypeSampledImage, _, _, Some(id)) => {
+                globals.image_kinds.insert(id, DescriptorKind::CombinedImageSampler);
+            }
+            (spirv::Op::TypeImage, _, _, Some(id)) => {
+                // images with a `Sampled` operand of 2 are used without a sampler.
+                let kind = match o.get(5) {
+                    Some(&Operand::LiteralInt32(2)) => DescriptorKind::StorageImage,
+                    _ => DescriptorKind::SampledImage,
+                };
+
+                globals.image_kinds.insert(id, kind);
+            }
+            (spirv::Op::Variable, Some(&Operand::StorageClass(storage_class)), _, Some(id)) => {
+                let pointee = match inst.result_type.and_then(|t| pointers.get(&t)) {
+                    Some(&(_, pointee)) => pointee,
+                    None => continue,
+                };
+
+                globals.variable(&a, id, storage_class, pointee, &lookup);
+            }
+            (spirv::Op::SpecConstantTrue, _, _, Some(id)) |
+            (spirv::Op::SpecConstantFalse, _, _, Some(id)) |
+            (spirv::Op::SpecConstant, _, _, Some(id)) => {
+                if let Some(Some(spec_id)) = a.get(id, Decoration::SpecId) {
+                    spec_constants.push(SpecConstantInfo {
+                        name: a.name(id),
+                        id: spec_id,
+                        ty: lookup(inst.result_type.ok_or(ErrorKind::NoType)?),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut out = Vec::new();
+
+    for inst in &module.entry_points {
+        let o = &inst.operands;
+
+        let (model, function, name) = match (o.get(0), o.get(1), o.get(2)) {
+            (Some(&Operand::ExecutionModel(model)),
+             Some(&Operand::IdRef(function)),
+             Some(&Operand::LiteralString(ref name))) => (model, function, name),
+            _ => continue,
+        };
+
+        let interface = &o[3..];
+
+        let kind = match model {
+            ExecutionModel::Vertex => ShaderKind::Vertex,
+            ExecutionModel::Fragment => ShaderKind::Fragment,
+            other => {
+                return Err(ErrorKind::UnsupportedExecutionModel(format!("{:?}", other)).into());
+            }
+        };
+
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+
+        for operand in interface {
+            let id = match *operand {
+                Operand::IdRef(id) => id,
+                _ => continue,
+            };
+
+            match globals.variables.get(&id) {
+                Some(&(StorageClass::Input, ref var)) => inputs.push(var.clone()),
+                Some(&(StorageClass::Output, ref var)) => outputs.push(var.clone()),
+                _ => {}
+            }
+        }
+
+        let used = used_ids(module, function);
+        let mut descriptor_sets = Vec::new();
+
+        for &(id, set, ref binding) in &globals.uniforms {
+            if used.contains(&id) {
+                add_binding(&mut descriptor_sets, set, binding.clone());
+            }
+        }
+
+        let push_constants = globals
+            .push_constants
+            .iter()
+            .filter(|p| used.contains(&p.0))
+            .map(|p| p.1.clone())
+            .collect();
+
+        inputs.sort_by_key(|v| v.location);
+        outputs.sort_by_key(|v| v.location);
+        descriptor_sets.sort_by_key(|s| s.set);
+
+        for set in &mut descriptor_sets {
+            set.bindings.sort_by_key(|b| b.binding);
+        }
+
+        out.push(EntryPointInfo {
+            name: name.clone(),
+            kind: kind,
+            inputs: inputs,
+            outputs: outputs,
+            descriptor_sets: descriptor_sets,
+            push_constants: push_constants,
+            spec_constants: spec_constants.clone(),
+        });
+    }
+
+    Ok(out)
+}
+
+/// Ids used by the function with the given id, and every function it calls.
+fn used_ids(module: &Module, function: Word) -> HashSet<Word> {
+    let mut used = HashSet::new();
+    let mut queue = vec![function];
+
+    while let Some(id) = queue.pop() {
+        let function = module.functions.iter().find(|f| {
+            f.def.as_ref().and_then(|d| d.result_id) == Some(id)
+        });
+
+        let function = match function {
+            Some(function) => function,
+            None => continue,
+        };
+
+        for block in &function.basic_blocks {
+            for inst in &block.instructions {
+                for operand in &inst.operands {
+                    if let Operand::IdRef(id) = *operand {
+                        if used.insert(id) && inst.class.opcode == spirv::Op::FunctionCall {
+                            queue.push(id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    used
+}
+
+/// Global variables of a module.
+#[derive(Default)]
+struct Globals {
+    /// Input and output variables, by id.
+    variables: HashMap<Word, (StorageClass, InterfaceVarInfo)>,
+    /// Descriptor bindings of global variables, as id, descriptor set and binding.
+    uniforms: Vec<(Word, u32, DescriptorBindingInfo)>,
+    /// Push constant variables, by id.
+    push_constants: Vec<(Word, PushConstantInfo)>,
+    /// Kinds of descriptor of sampler and image types, by id.
+    image_kinds: HashMap<Word, DescriptorKind>,
+}
+
+impl Globals {
+    /// Reflect a single global variable.
+    fn variable<L>(
+        &mut self,
+        a: &Annotations,
+        id: Word,
+        storage_class: StorageClass,
+        pointee: Word,
+        lookup: &L,
+    ) where
+        L: Fn(Word) -> TypeInfo,
+    {
+        match storage_class {
+            StorageClass::Input | StorageClass::Output => {
+                // built-ins have no location.
+                let location = match a.get(id, Decoration::Location) {
+                    Some(Some(location)) => location,
+                    _ => return,
+                };
+
+                let ty = lookup(pointee);
+
+                let interpolation = if a.has(id, Decoration::Flat) {
+                    Interpolation::Flat
+                } else if a.has(id, Decoration::NoPerspective) {
+                    Interpolation::NoPerspective
+                } else {
+                    Interpolation::Smooth
+                };
+
+                let sampling = if a.has(id, Decoration::Centroid) {
+                    Sampling::Centroid
+                } else if a.has(id, Decoration::Sample) {
+                    Sampling::Sample
+                } else {
+                    Sampling::Center
+                };
+
+                self.variables.insert(
+                    id,
+                    (
+                        storage_class,
+                        InterfaceVarInfo {
+                            name: a.name(id),
+                            location: location,
+                            location_count: ty.location_slots(),
+                            component: a.get(id, Decoration::Component).and_then(|c| c),
+                            interpolation: interpolation,
+                            sampling: sampling,
+                            ty: ty,
+                        },
+                    ),
+                );
+            }
+            StorageClass::Uniform | StorageClass::StorageBuffer => {
+                // before SPIR-V 1.3, storage buffers are uniform blocks decorated as BufferBlock.
+                let kind = if storage_class == StorageClass::StorageBuffer ||
+                    a.has(pointee, Decoration::BufferBlock)
+                {
+                    DescriptorKind::StorageBuffer
+                } else {
+                    DescriptorKind::UniformBuffer
+                };
+
+                self.binding(a, id, kind, lookup(pointee));
+            }
+            StorageClass::UniformConstant => {
+                // arrays of images and samplers are not reflected.
+                if let Some(&kind) = self.image_kinds.get(&pointee) {
+                    self.binding(a, id, kind, lookup(pointee));
+                }
+            }
+            StorageClass::PushConstant => {
+                let ty = lookup(pointee);
+
+                // the range starts at the first member, and ends with the block.
+                let offset = match ty {
+                    TypeInfo::Struct(ref st) => st.members.iter().map(|m| m.offset).min(),
+                    _ => None,
+                }.unwrap_or(0);
+
+                self.push_constants.push((
+                    id,
+                    PushConstantInfo {
+                        name: a.name(id),
+                        offset: offset,
+                        size: type_size(&ty) - offset,
+                        ty: ty,
+                    },
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    /// Add the descriptor binding of a global variable.
+    fn binding(&mut self, a: &Annotations, id: Word, kind: DescriptorKind, ty: TypeInfo) {
+        let set = a.get(id, Decoration::DescriptorSet).and_then(|s| s).unwrap_or(0);
+        let binding = a.get(id, Decoration::Binding).and_then(|b| b).unwrap_or(0);
+
+        self.uniforms.push((
+            id,
+            set,
+            DescriptorBindingInfo {
+                binding: binding,
+                name: a.name(id),
+                kind: kind,
+                ty: ty,
+            },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DescriptorKind, TypeInfo, from_module};
+    use access::AccessTrait;
+    use function::FunctionBuilder;
+    use input_var::InputVar;
+    use ops::{load, mul, store, vec3_to_vec4};
+    use output_var::OutputVar;
+    use shader::{Shader, ShaderKind};
+    use std::sync::Arc;
+    use struct_member::StructMember;
+    use types::{Struct, mat4, vec3, vec4};
+    use uniform_var::UniformVar;
+
+    #[test]
+    fn test_from_module() {
+        let mut shader = Shader::new();
+
+        let global = Struct::new(
+            "Global",
+            vec![
+                Arc::new(StructMember::new("view", Arc::new(mat4()), 0)),
+                Arc::new(StructMember::new("color", Arc::new(vec4()), 1)),
+            ],
+        );
+
+        let global = UniformVar::new("global", global, 1, 2);
+        let position = InputVar::new("position", vec3(), 2);
+        let v_position = OutputVar::new("v_position", vec4(), 3);
+
+        let mut main = FunctionBuilder::new("main");
+        let view = load(global.access_member(StructMember::new("view", Arc::new(mat4()), 0)));
+        let pos = vec3_to_vec4(load(position.clone()), 1.0);
+        main.op(store(v_position.clone(), mul(view, pos)));
+        shader.entry_point_auto(ShaderKind::Vertex, main.returns_void()).unwrap();
+
+        let info = from_module(&shader.module()).unwrap();
+        assert_eq!(info.len(), 1);
+
+        let main = &info[0];
+        assert_eq!(main.name, "main");
+        assert_eq!(main.kind, ShaderKind::Vertex);
+
+        let inputs: Vec<_> = main.inputs.iter().map(|v| (v.name.as_str(), v.location)).collect();
+        assert_eq!(inputs, vec![("position", 2)]);
+
+        let outputs: Vec<_> = main.outputs.iter().map(|v| (v.name.as_str(), v.location)).collect();
+        assert_eq!(outputs, vec![("v_position", 3)]);
+
+        assert_eq!(main.descriptor_sets.len(), 1);
+        assert_eq!(main.descriptor_sets[0].set, 1);
+        assert_eq!(main.descriptor_sets[0].bindings.len(), 1);
+
+        let binding = &main.descriptor_sets[0].bindings[0];
+        assert_eq!(binding.binding, 2);
+        assert_eq!(binding.name, "global");
+        assert_eq!(binding.kind, DescriptorKind::UniformBuffer);
+
+        let layout = match binding.ty {
+            TypeInfo::Struct(ref layout) => layout,
+            ref other => panic!("expected struct, got {:?}", other),
+        };
+
+        let members: Vec<_> = layout.members.iter().map(|m| (m.name.as_str(), m.offset)).collect();
+        assert_eq!(layout.name, "Global");
+        assert_eq!(members, vec![("view", 0), ("color", 64)]);
+        assert_eq!(layout.size, 80);
+    }
+}
@@ -1,17 +1,27 @@
+use super::debug_info;
+use super::decorations;
 use super::errors::*;
 use super::function::Function;
+use super::interface::{self, Interface};
+use super::locations::{LocationMode, Locations};
 use super::op::Op;
 use super::op_key::OpKey;
+use super::optimize;
+use super::reflect::{self, EntryPointInfo};
 use super::rspirv;
-use super::spirv::{ExecutionModel, StorageClass, Word};
+use super::source_location::SourceLocation;
+use super::spirv::{BuiltIn, Capability, ExecutionMode, ExecutionModel, SourceLanguage,
+                   StorageClass, Word};
 use super::spirv_type::SpirvType;
+use super::target::{Env, Target};
 use super::types::{Float, UnsignedInteger};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Kind of shader that can be built.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum ShaderKind {
     /// A vertex shader kind.
     Vertex,
@@ -31,12 +41,60 @@ impl ShaderKind {
     }
 }
 
+/// Debug information emitted for the source of a shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugInfo {
+    /// Only names of variables and types.
+    Names,
+    /// `OpLine` for every operation, referring to the Rust source where it was constructed.
+    Lines,
+    /// Like `Lines`, but using the `NonSemantic.Shader.DebugInfo.100` extended instruction set.
+    NonSemantic,
+}
+
+/// An entry point which has been added to a shader.
+#[derive(Debug)]
+pub(crate) struct EntryPoint {
+    /// Name of the entry point.
+    pub name: String,
+    /// Kind of the entry point.
+    pub kind: ShaderKind,
+    /// Interface of the entry point.
+    pub interface: Vec<Arc<Op>>,
+    /// The function of the entry point.
+    pub function: Function,
+    /// Reflection of the entry point.
+    pub info: EntryPointInfo,
+}
+
 /// Shader being built.
 pub struct Shader {
     /// Internal builder
     pub(crate) builder: rspirv::mr::Builder,
     /// Cached types, to only initialize each type once.
     op_cache: HashMap<OpKey, Word>,
+    /// The imported GLSL.std.450 extended instruction set.
+    pub(crate) glsl_std_450: Word,
+    /// Capabilities declared by the shader.
+    capabilities: HashSet<Capability>,
+    /// Extensions declared by the shader.
+    extensions: HashSet<String>,
+    /// Environment the shader is built for.
+    target: Target,
+    /// Debug information to emit.
+    debug_info: DebugInfo,
+    /// `OpString` declared for each file name.
+    strings: HashMap<&'static str, Word>,
+    /// Locations of the operations currently being emitted, innermost last.
+    lines: Vec<SourceLocation>,
+    /// Location of the last `OpLine` in the current block.
+    current_line: Option<SourceLocation>,
+    /// How locations are determined for interface variables.
+    location_mode: LocationMode,
+    /// Locations of interface variables.
+    pub(crate) locations: Locations,
+    /// Entry points added to the shader.
+    pub(crate) entry_points: Vec<EntryPoint>,
     #[cfg(feature = "vulkan")]
     vulkan_shader_interfaces: Vec<self::vulkan::ShaderInterface>,
 }
@@ -49,21 +107,39 @@ impl fmt::Debug for Shader {
 
 
 impl Shader {
-    /// Create a new shader builder.
+    /// Create a new shader builder, for the default target.
     pub fn new() -> Shader {
-        use super::spirv::Capability;
+        Shader::with_target(Target::default())
+    }
+
+    /// Create a new shader builder, for the given target.
+    pub fn with_target(target: Target) -> Shader {
         use super::spirv::AddressingModel;
         use super::spirv::MemoryModel;
 
         let mut builder = rspirv::mr::Builder::new();
 
         builder.capability(Capability::Shader);
-        builder.ext_inst_import(String::from("GLSL.std.450"));
+        let glsl_std_450 = builder.ext_inst_import(String::from("GLSL.std.450"));
         builder.memory_model(AddressingModel::Logical, MemoryModel::GLSL450);
 
+        let mut capabilities = HashSet::new();
+        capabilities.insert(Capability::Shader);
+
         Shader {
             builder: builder,
             op_cache: HashMap::new(),
+            glsl_std_450: glsl_std_450,
+            capabilities: capabilities,
+            extensions: HashSet::new(),
+            target: target,
+            debug_info: DebugInfo::Names,
+            strings: HashMap::new(),
+            lines: Vec::new(),
+            current_line: None,
+            location_mode: LocationMode::Explicit,
+            locations: Locations::default(),
+            entry_points: Vec::new(),
             #[cfg(feature = "vulkan")]
             vulkan_shader_interfaces: Vec::new(),
         }
@@ -82,6 +158,152 @@ impl Shader {
         Ok(id)
     }
 
+    /// Declare that the shader requires the given capability.
+    pub(crate) fn capability(&mut self, capability: Capability) {
+        if self.capabilities.insert(capability) {
+            self.builder.capability(capability);
+        }
+    }
+
+    /// Declare that the shader uses the given extension.
+    pub(crate) fn extension(&mut self, extension: &str) {
+        if self.extensions.insert(extension.to_string()) {
+            self.builder.extension(extension.to_string());
+        }
+    }
+
+    /// Set which debug information is emitted.
+    ///
+    /// Must be called before any entry points are added. `DebugInfo::NonSemantic` requires a target
+    /// supporting SPIR-V 1.6, or allowing the `SPV_KHR_non_semantic_info` extension.
+    pub fn set_debug_info(&mut self, debug_info: DebugInfo) -> Result<()> {
+        const NON_SEMANTIC_INFO: &'static str = "SPV_KHR_non_semantic_info";
+
+        if !self.entry_points.is_empty() {
+            return Err(ErrorKind::EntryPointsAdded("debug info").into());
+        }
+
+        if debug_info == DebugInfo::NonSemantic && !self.target.supports_spirv(1, 6) {
+            if !self.target.allows_extension(NON_SEMANTIC_INFO) {
+                return Err(
+                    ErrorKind::UnsupportedByTarget(
+                        String::from("non-semantic debug info"),
+                        format!("SPIR-V 1.6 or {}", NON_SEMANTIC_INFO),
+                    ).into(),
+                );
+            }
+
+            self.extension(NON_SEMANTIC_INFO);
+        }
+
+        if debug_info != DebugInfo::Names && self.debug_info == DebugInfo::Names {
+            let generator = self.string(concat!("spvc-shader ", env!("CARGO_PKG_VERSION")));
+            self.builder.source(SourceLanguage::Unknown, 0, Some(generator), None);
+        }
+
+        self.debug_info = debug_info;
+        Ok(())
+    }
+
+    /// Check if `OpLine` instructions are emitted for operations.
+    pub(crate) fn emits_lines(&self) -> bool {
+        self.debug_info != DebugInfo::Names
+    }
+
+    /// Declare a string, used as the name of a file.
+    fn string(&mut self, string: &'static str) -> Word {
+        if let Some(id) = self.strings.get(string) {
+            return *id;
+        }
+
+        let id = self.builder.string(string.to_string());
+        self.strings.insert(string, id);
+        id
+    }
+
+    fn line(&mut self, location: SourceLocation) {
+        if self.current_line == Some(location) {
+            return;
+        }
+
+        let file = self.string(location.file);
+        self.builder.line(file, location.line, location.column);
+        self.current_line = Some(location);
+    }
+
+    /// Attribute the following instructions to the given location, until `pop_line` is called.
+    pub(crate) fn push_line(&mut self, location: SourceLocation) {
+        self.lines.push(location);
+        self.line(location);
+    }
+
+    /// Attribute the following instructions to the location before the last `push_line`.
+    pub(crate) fn pop_line(&mut self) {
+        self.lines.pop();
+
+        if let Some(&location) = self.lines.last() {
+            self.line(location);
+        }
+    }
+
+    /// Forget the location of the last `OpLine`, since it only applies to its block.
+    pub(crate) fn reset_line(&mut self) {
+        self.current_line = None;
+    }
+
+    /// The target the shader is built for.
+    pub fn target(&self) -> &Target {
+        &self.target
+    }
+
+    /// Check that the given storage class is available for the target, declaring any extension
+    /// it requires.
+    pub(crate) fn require_storage_class(&mut self, storage_class: StorageClass) -> Result<()> {
+        const STORAGE_BUFFER: &'static str = "SPV_KHR_storage_buffer_storage_class";
+
+        if storage_class != StorageClass::StorageBuffer {
+            return Ok(());
+        }
+
+        // OpenGL declares storage buffers as uniforms decorated with BufferBlock.
+        if self.target.env != Env::Vulkan {
+            return Err(
+                ErrorKind::UnsupportedByTarget(
+                    format!("storage class {:?}", storage_class),
+                    String::from("Vulkan environment"),
+                ).into(),
+            );
+        }
+
+        if self.target.supports_spirv(1, 3) {
+            return Ok(());
+        }
+
+        if self.target.allows_extension(STORAGE_BUFFER) {
+            self.extension(STORAGE_BUFFER);
+            return Ok(());
+        }
+
+        Err(
+            ErrorKind::UnsupportedByTarget(
+                format!("storage class {:?}", storage_class),
+                format!("SPIR-V 1.3 or {}", STORAGE_BUFFER),
+            ).into(),
+        )
+    }
+
+    /// Declare the capabilities required by the given built-in.
+    pub(crate) fn require_built_in(&mut self, built_in: BuiltIn) {
+        match built_in {
+            BuiltIn::ClipDistance => self.capability(Capability::ClipDistance),
+            BuiltIn::CullDistance => self.capability(Capability::CullDistance),
+            BuiltIn::SampleId | BuiltIn::SamplePosition => {
+                self.capability(Capability::SampleRateShading)
+            }
+            _ => {}
+        }
+    }
+
     pub(crate) fn constant_u32(&mut self, value: u32) -> Result<Word> {
         let integer_type = UnsignedInteger.register_type(self)?;
 
@@ -111,6 +333,8 @@ impl Shader {
         storage_class: StorageClass,
         pointee_type: Word,
     ) -> Result<Word> {
+        self.require_storage_class(storage_class)?;
+
         self.cache_op(
             OpKey::Pointer {
                 storage_class: storage_class,
@@ -134,25 +358,102 @@ impl Shader {
         self.builder.name(id, name.to_string());
     }
 
+    /// Set how locations of input and output variables are determined.
+    ///
+    /// With `LocationMode::Auto`, variables without an explicit location are assigned one when
+    /// they are part of the interface of an entry point.
+    pub fn set_location_mode(&mut self, location_mode: LocationMode) {
+        self.location_mode = location_mode;
+    }
+
     /// Create a new entry-point to a shader.
+    ///
+    /// Locations are assigned to input and output variables in the interface according to the
+    /// current location mode, and it is an error for any two of them to overlap.
+    ///
+    /// A shader can have several entry points, which share types and uniform variables, as long as
+    /// no two of them have the same name and kind.
+    ///
+    /// Input, output and built-in variables used by the function are added to the interface if
+    /// they are missing from it, and a warning is logged for each variable where the given
+    /// interface disagrees with the function. For SPIR-V 1.4 and later, used uniform variables are
+    /// added as well.
     pub fn entry_point(
         &mut self,
         kind: ShaderKind,
         function: Function,
-        interface: Vec<Rc<Op>>,
+        interface: Vec<Arc<Op>>,
+    ) -> Result<()> {
+        let used = interface::collect(function.ops(), self.target.supports_spirv(1, 4));
+        let mut interface = interface;
+
+        for op in &interface {
+            let var = match op.as_interface() {
+                Some(Interface::Uniform(_)) | None => continue,
+                Some(var) => var,
+            };
+
+            if !used.iter().any(|u| interface::same_var(u, op)) {
+                warn!("{}: `{}` is not used by the function", function.name, var.name());
+            }
+        }
+
+        for op in used {
+            if !interface.iter().any(|i| interface::same_var(i, &op)) {
+                if let Some(var) = op.as_interface() {
+                    warn!("{}: `{}` is missing from the interface", function.name, var.name());
+                }
+
+                interface.push(op);
+            }
+        }
+
+        self.add_entry_point(kind, function, interface)
+    }
+
+    /// Create a new entry-point to a shader, with an interface of every input, output and
+    /// built-in variable used by the function, and every uniform variable for SPIR-V 1.4 and later.
+    pub fn entry_point_auto(&mut self, kind: ShaderKind, function: Function) -> Result<()> {
+        let interface = interface::collect(function.ops(), self.target.supports_spirv(1, 4));
+        self.add_entry_point(kind, function, interface)
+    }
+
+    fn add_entry_point(
+        &mut self,
+        kind: ShaderKind,
+        function: Function,
+        interface: Vec<Arc<Op>>,
     ) -> Result<()> {
+        self.target.validate()?;
+
+        if self.entry_points.iter().any(
+            |e| e.name == function.name && e.kind == kind,
+        )
+        {
+            return Err(ErrorKind::DuplicateEntryPoint(function.name.clone(), kind).into());
+        }
+
+        self.locations.assign(self.location_mode, &interface)?;
+        decorations::check_interface(kind, &interface)?;
+
         let interface_words = {
             let mut out = Vec::new();
 
             for i in &interface {
-                out.push(i.register_op(self)?.op_id(self)?.ok_or(ErrorKind::NoOp)?);
+                let word = i.register_op(self)?.op_id(self)?.ok_or(ErrorKind::NoOp)?;
+
+                // distinct operations for the same variable are registered as the same id.
+                if !out.contains(&word) {
+                    out.push(word);
+                }
             }
 
             out
         };
 
         let name = function.name.clone();
-        let id = function.register_function(self)?;
+        let info = reflect::entry_point_info(&name, kind, &function, &interface, &self.locations)?;
+        let id = function.clone().register_function(self)?;
 
         self.builder.entry_point(
             kind.as_execution_model(),
@@ -161,34 +462,76 @@ impl Shader {
             &interface_words,
         );
 
+        if kind == ShaderKind::Fragment {
+            // required for all fragment shaders under vulkan.
+            self.builder.execution_mode(id, ExecutionMode::OriginUpperLeft, &[]);
+        }
+
         #[cfg(feature = "vulkan")]
         {
-            let interface = self::vulkan::interface_from_ops(name.clone(), kind, &interface)?;
+            let interface = self::vulkan::interface_from_info(&info)?;
             self.vulkan_shader_interfaces.push(interface);
         }
 
+        self.entry_points.push(EntryPoint {
+            name: name,
+            kind: kind,
+            interface: interface,
+            function: function,
+            info: info,
+        });
+
         Ok(())
     }
 
+    /// Reflection of all entry points added to the shader.
+    pub fn reflect(&self) -> Vec<&EntryPointInfo> {
+        self.entry_points.iter().map(|e| &e.info).collect()
+    }
+
     /// Convert the shader being built to a SPIR-V module.
     pub fn module(self) -> rspirv::mr::Module {
-        self.builder.module()
+        let version = self.target.version_word();
+        let debug_info = self.debug_info;
+        let mut module = self.builder.module();
+
+        if let Some(ref mut header) = module.header {
+            header.version = version;
+        }
+
+        // strings and sources have to come before names.
+        debug_info::sort_debugs(&mut module);
+
+        if debug_info == DebugInfo::NonSemantic {
+            debug_info::to_non_semantic(&mut module);
+        }
+
+        module
+    }
+
+    /// Convert the shader being built to a SPIR-V module, and optimize it.
+    pub fn optimized_module(self, options: optimize::Options) -> rspirv::mr::Module {
+        let mut module = self.module();
+        optimize::optimize(&mut module, options);
+        module
     }
 }
 
 #[cfg(feature = "vulkan")]
 mod vulkan {
-    use super::{Op, Rc};
     use errors::*;
-    use interface::Interface;
+    use reflect::{DescriptorKind, EntryPointInfo, TypeInfo};
     use rspirv::binary::Assemble;
     use std::borrow::Cow;
     use std::cmp;
     use std::collections::HashMap;
     use std::ffi::{CStr, CString};
+    use std::mem;
     use std::slice;
     use std::sync::Arc;
-    use vulkano::descriptor::descriptor::{DescriptorDesc, ShaderStages};
+    use vulkano::descriptor::descriptor::{DescriptorBufferDesc, DescriptorDesc, DescriptorDescTy,
+                                          ShaderStages};
+    use vulkano::format::Format;
     use vulkano::descriptor::pipeline_layout::{PipelineLayoutDesc, PipelineLayoutDescPcRange};
     use vulkano::pipeline::shader::{GraphicsEntryPoint, GraphicsShaderType, ShaderInterfaceDef,
                                     ShaderInterfaceDefEntry};
@@ -226,18 +569,20 @@ mod vulkan {
     impl super::Shader {
         /// Convert the shader into a VulkanModule.
         pub fn vulkan_shader_module(
-            self,
+            mut self,
             device: ::std::sync::Arc<::vulkano::device::Device>,
         ) -> Result<VulkanShader> {
             use vulkano::pipeline::shader::ShaderModule;
 
             let mut entry_points = HashMap::new();
+            let interfaces = mem::replace(&mut self.vulkan_shader_interfaces, Vec::new());
 
-            for interface in self.vulkan_shader_interfaces {
-                entry_points.insert(interface.name.clone(), interface);
+            for interface in interfaces {
+                entry_points.insert((interface.name.clone(), interface.kind), interface);
             }
 
-            let module = self.builder.module();
+            // the module is finished like any other, with its header and debug information.
+            let module = self.module();
             let code = module.assemble();
 
             let module = unsafe {
@@ -265,15 +610,16 @@ mod vulkan {
     #[derive(Debug)]
     pub struct VulkanShader {
         module: Arc<::vulkano::pipeline::shader::ShaderModule>,
-        entry_points: HashMap<String, ShaderInterface>,
+        entry_points: HashMap<(String, super::ShaderKind), ShaderInterface>,
     }
 
     impl VulkanShader {
         pub fn graphics_entry_point(
             &self,
             name: &str,
+            kind: super::ShaderKind,
         ) -> Option<GraphicsEntryPoint<(), ShaderInput, ShaderOutput, ShaderLayout>> {
-            if let Some(interface) = self.entry_points.get(name) {
+            if let Some(interface) = self.entry_points.get(&(name.to_string(), kind)) {
                 let entry_point = unsafe {
                     let name = CStr::from_ptr(interface.name_cstring.as_ptr());
 
@@ -355,71 +701,107 @@ mod vulkan {
         }
     }
 
-    pub fn interface_from_ops(
-        name: String,
-        kind: super::ShaderKind,
-        ops: &Vec<Rc<Op>>,
-    ) -> Result<ShaderInterface> {
-        use self::Interface::*;
+    /// Format of an interface variable of the given type.
+    fn vulkano_format(ty: &TypeInfo) -> Option<Format> {
+        use vulkano::format::Format::*;
+
+        match *ty {
+            TypeInfo::Float => Some(R32Sfloat),
+            TypeInfo::UnsignedInteger => Some(R32Uint),
+            TypeInfo::Vector {
+                ref component,
+                count,
+            } => {
+                match (component.as_ref(), count) {
+                    (&TypeInfo::Float, 2) => Some(R32G32Sfloat),
+                    (&TypeInfo::Float, 3) => Some(R32G32B32Sfloat),
+                    (&TypeInfo::Float, 4) => Some(R32G32B32A32Sfloat),
+                    (&TypeInfo::UnsignedInteger, 2) => Some(R32G32Uint),
+                    (&TypeInfo::UnsignedInteger, 3) => Some(R32G32B32Uint),
+                    (&TypeInfo::UnsignedInteger, 4) => Some(R32G32B32A32Uint),
+                    _ => None,
+                }
+            }
+            // each column occupies a location of its own.
+            TypeInfo::Matrix { ref column, .. } => vulkano_format(column),
+            _ => None,
+        }
+    }
+
+    pub fn interface_from_info(info: &EntryPointInfo) -> Result<ShaderInterface> {
+        let stages = info.kind.to_shader_stages();
 
-        let mut num_sets = 0usize;
         let mut bindings = HashMap::new();
         let mut descriptors = HashMap::new();
+
+        for set in &info.descriptor_sets {
+            let set_index = set.set as usize;
+
+            for b in &set.bindings {
+                let binding = b.binding as usize;
+
+                let storage = b.kind == DescriptorKind::StorageBuffer;
+
+                // the dimensions of images are not reflected.
+                let ty = match b.kind {
+                    DescriptorKind::UniformBuffer | DescriptorKind::StorageBuffer => {
+                        DescriptorDescTy::Buffer(DescriptorBufferDesc {
+                            dynamic: Some(false),
+                            storage: storage,
+                        })
+                    }
+                    DescriptorKind::Sampler => DescriptorDescTy::Sampler,
+                    _ => {
+                        return Err(
+                            ErrorKind::UnsupportedByBackend(
+                                "vulkano",
+                                format!("image binding `{}`", b.name),
+                            ).into(),
+                        );
+                    }
+                };
+
+                let descriptor = DescriptorDesc {
+                    ty: ty,
+                    array_count: 1,
+                    stages: stages.clone(),
+                    readonly: !storage,
+                };
+
+                let count = bindings.entry(set_index).or_insert(0usize);
+                *count = cmp::max(*count, binding + 1);
+                descriptors.insert((set_index, binding), descriptor);
+            }
+        }
+
+        let num_sets = info.descriptor_sets
+            .iter()
+            .map(|s| s.set as usize + 1)
+            .max()
+            .unwrap_or(0);
+
         let mut input = Vec::new();
         let mut output = Vec::new();
 
-        let stages = kind.to_shader_stages();
-
-        for op in ops {
-            let interface = op.as_interface().ok_or(ErrorKind::NotInterface)?;
-
-            let (dest, location, name, dest_type) = match interface {
-                Input(var) => (
-                    &mut input,
-                    var.location,
-                    var.name.to_owned(),
-                    &var.pointer.pointee_type,
-                ),
-                Output(var) => (
-                    &mut output,
-                    var.location,
-                    var.name.to_owned(),
-                    &var.pointer.pointee_type,
-                ),
-                Uniform(var) => {
-                    let descriptor = var.as_vulkan_descriptor(&stages).ok_or(
-                        ErrorKind::IllegalInterfaceType,
-                    )?;
-
-                    let set = var.set as usize;
-                    let binding = var.binding as usize;
-
-                    num_sets = cmp::max(set, num_sets);
-                    bindings.insert(set, binding);
-                    descriptors.insert((set, binding), descriptor);
-                    continue;
-                }
-                BuiltIn => continue,
-            };
+        for (dest, vars) in vec![(&mut input, &info.inputs), (&mut output, &info.outputs)] {
+            for var in vars {
+                let format = vulkano_format(&var.ty).ok_or(ErrorKind::IllegalInterfaceType)?;
 
-            let format = dest_type.as_vulkano_format().ok_or(
-                ErrorKind::IllegalInterfaceType,
-            )?;
-
-            dest.push(ShaderInterfaceDefEntry {
-                location: location..location + 1,
-                format: format,
-                name: Some(Cow::Owned(name)),
-            });
+                dest.push(ShaderInterfaceDefEntry {
+                    location: var.location..var.location + var.location_count,
+                    format: format,
+                    name: Some(Cow::Owned(var.name.clone())),
+                });
+            }
         }
 
         let num_push_constants_ranges = 0;
         let push_constants_range = HashMap::new();
 
         Ok(ShaderInterface {
-            name: name.clone(),
-            name_cstring: CString::new(name.clone())?,
-            kind: kind,
+            name: info.name.clone(),
+            name_cstring: CString::new(info.name.clone())?,
+            kind: info.kind,
             input: ShaderInput { input: input },
             output: ShaderOutput { output: output },
             layout: ShaderLayout {
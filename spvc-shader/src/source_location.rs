@@ -0,0 +1,39 @@
+use std::fmt;
+
+/// A location in the source code used to generate a shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    /// File of the location.
+    pub file: &'static str,
+    /// Line of the location, starting at 1.
+    pub line: u32,
+    /// Column of the location, starting at 1.
+    pub column: u32,
+}
+
+impl SourceLocation {
+    /// Create a new source location.
+    pub fn new(file: &'static str, line: u32, column: u32) -> SourceLocation {
+        SourceLocation {
+            file: file,
+            line: line,
+            column: column,
+        }
+    }
+
+    /// The location in Rust source which called the current function.
+    ///
+    /// Functions calling this should be marked with `#[track_caller]`, so that the location is
+    /// that of the code building the shader.
+    #[track_caller]
+    pub fn caller() -> SourceLocation {
+        let location = ::std::panic::Location::caller();
+        SourceLocation::new(location.file(), location.line(), location.column())
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
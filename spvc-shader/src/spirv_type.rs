@@ -2,13 +2,47 @@ use super::errors::*;
 use super::matrix_dims::MatrixDims;
 use super::pointer::Pointer;
 use super::shader::Shader;
-use super::spirv::Word;
+use super::spirv::{StorageClass, Word};
 use super::types::{Bool, Float, Matrix, Struct, UnsignedInteger, Vector};
 use super::vector_dims::VectorDims;
+use std::borrow::Cow;
 use std::fmt;
 
+/// Structural description of a type, which can be compared and hashed.
+///
+/// Two types match if and only if their keys are equal, and they are registered as the same
+/// SPIR-V type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeKey {
+    /// The result of operations without a value.
+    None,
+    /// A boolean.
+    Bool,
+    /// A 32-bit float.
+    Float,
+    /// A 32-bit unsigned integer.
+    UnsignedInteger,
+    /// A vector, with its component type and count.
+    Vector(Box<TypeKey>, u32),
+    /// A matrix, with its column type and count.
+    Matrix(Box<TypeKey>, u32),
+    /// A struct, with its name and the name and type of each member.
+    Struct(Cow<'static, str>, Vec<(Cow<'static, str>, TypeKey)>),
+    /// A pointer, with its storage class and pointee type.
+    Pointer(StorageClass, Box<TypeKey>),
+    /// A type without a structural description, identified by how it is displayed.
+    Opaque(String),
+}
+
+impl TypeKey {
+    /// Structural description of the given type.
+    pub fn of(ty: &SpirvType) -> TypeKey {
+        ty.key()
+    }
+}
+
 /// Reflects a type in SPIR-V.
-pub trait SpirvType: fmt::Debug {
+pub trait SpirvType: fmt::Debug + Send + Sync {
     /// Display a representation of this type.
     fn display(&self) -> String;
 
@@ -84,8 +118,41 @@ pub trait SpirvType: fmt::Debug {
     /// Width in bytes of the type.
     fn width(&self) -> u32;
 
+    /// Number of consecutive locations this type occupies when used as an interface variable.
+    fn location_slots(&self) -> u32 {
+        1
+    }
+
+    /// Structural description of this type.
+    fn key(&self) -> TypeKey {
+        if let Some(pointer) = self.as_pointer() {
+            let pointee_type = pointer.pointee_type.key();
+            return TypeKey::Pointer(pointer.storage_class(), Box::new(pointee_type));
+        }
+
+        if let Some(matrix) = self.as_matrix() {
+            return TypeKey::Matrix(Box::new(matrix.column_type.key()), matrix.column_count);
+        }
+
+        if let Some(vector) = self.as_vector() {
+            return TypeKey::Vector(Box::new(vector.component.key()), vector.component_count);
+        }
+
+        if self.as_float().is_some() {
+            TypeKey::Float
+        } else if self.as_unsigned_integer().is_some() {
+            TypeKey::UnsignedInteger
+        } else if self.as_bool().is_some() {
+            TypeKey::Bool
+        } else {
+            TypeKey::Opaque(self.display())
+        }
+    }
+
     /// Check if this type matches another type.
-    fn matches(&self, other: &SpirvType) -> bool;
+    fn matches(&self, other: &SpirvType) -> bool {
+        self.key() == other.key()
+    }
 
     /// Current type as vulkano format.
     #[cfg(feature = "vulkan")]
@@ -114,8 +181,8 @@ impl SpirvType for NoType {
         Some(*self)
     }
 
-    fn matches(&self, other: &SpirvType) -> bool {
-        other.as_no_type().is_some()
+    fn key(&self) -> TypeKey {
+        TypeKey::None
     }
 }
 
@@ -125,7 +192,7 @@ pub trait WrapperType {
 
 impl<T> SpirvType for T
 where
-    T: fmt::Debug + WrapperType,
+    T: fmt::Debug + Send + Sync + WrapperType,
 {
     fn register_type(&self, shader: &mut Shader) -> Result<Word> {
         self.wrapped_type().register_type(shader)
@@ -139,8 +206,12 @@ where
         self.wrapped_type().width()
     }
 
-    fn matches(&self, other: &SpirvType) -> bool {
-        return self.wrapped_type().matches(other);
+    fn location_slots(&self) -> u32 {
+        self.wrapped_type().location_slots()
+    }
+
+    fn key(&self) -> TypeKey {
+        self.wrapped_type().key()
     }
 
     fn display(&self) -> String {
@@ -3,20 +3,33 @@
 //! These are typically generated using the glsl-struct-derive crate.
 
 use super::spirv_type::SpirvType;
-use std::rc::Rc;
+use std::borrow::Cow;
+use std::sync::Arc;
 
 /// Reflects a single struct member.
 #[derive(Debug)]
 pub struct StructMember {
     /// Name of the struct member.
-    pub name: &'static str,
+    pub name: Cow<'static, str>,
     /// Type of the struct member.
-    pub ty: Rc<SpirvType>,
+    pub ty: Arc<SpirvType>,
     /// Index of the struct member.
     pub index: u32,
 }
 
 impl StructMember {
+    /// Create a new struct member, with a name which may be constructed at runtime.
+    pub fn new<N>(name: N, ty: Arc<SpirvType>, index: u32) -> StructMember
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        StructMember {
+            name: name.into(),
+            ty: ty,
+            index: index,
+        }
+    }
+
     /// Check if this member matches another.
     pub fn matches(&self, other: &StructMember) -> bool {
         if self.name != other.name {
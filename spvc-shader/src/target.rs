@@ -0,0 +1,132 @@
+//! # Targets
+//!
+//! The environment a shader is built for, which determines the version of SPIR-V emitted and
+//! which capabilities, extensions and storage classes are available.
+
+use super::errors::*;
+
+/// Client API which consumes the shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Env {
+    /// Vulkan.
+    Vulkan,
+    /// OpenGL, through `GL_ARB_gl_spirv`.
+    OpenGl,
+}
+
+/// Environment a shader is built for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    /// Version of SPIR-V, as major and minor version.
+    pub spirv_version: (u8, u8),
+    /// Version of Vulkan, as major and minor version, if the environment is Vulkan.
+    pub vulkan_version: Option<(u32, u32)>,
+    /// Client API which consumes the shader.
+    pub env: Env,
+    /// SPIR-V extensions which may be declared by the shader.
+    pub extensions: Vec<String>,
+}
+
+impl Default for Target {
+    fn default() -> Target {
+        Target::vulkan_1_0()
+    }
+}
+
+impl Target {
+    /// Vulkan 1.0, with SPIR-V 1.0.
+    pub fn vulkan_1_0() -> Target {
+        Target {
+            spirv_version: (1, 0),
+            vulkan_version: Some((1, 0)),
+            env: Env::Vulkan,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// Vulkan 1.1, with SPIR-V 1.3.
+    pub fn vulkan_1_1() -> Target {
+        Target {
+            spirv_version: (1, 3),
+            vulkan_version: Some((1, 1)),
+            env: Env::Vulkan,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// OpenGL 4.5, with SPIR-V 1.0.
+    pub fn opengl_4_5() -> Target {
+        Target {
+            spirv_version: (1, 0),
+            vulkan_version: None,
+            env: Env::OpenGl,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// Allow the shader to declare the given extension.
+    pub fn with_extension(mut self, extension: &str) -> Target {
+        self.extensions.push(extension.to_string());
+        self
+    }
+
+    /// Check that the version of SPIR-V can be consumed by the client API.
+    pub fn validate(&self) -> Result<()> {
+        let max = match (self.env, self.vulkan_version) {
+            (Env::Vulkan, Some(version)) if version >= (1, 3) => (1, 6),
+            (Env::Vulkan, Some(version)) if version >= (1, 2) => (1, 5),
+            (Env::Vulkan, Some(version)) if version >= (1, 1) => (1, 3),
+            (Env::Vulkan, Some(_)) => (1, 0),
+            (Env::Vulkan, None) => {
+                return Err(
+                    ErrorKind::UnsupportedByTarget(
+                        String::from("Vulkan environment"),
+                        String::from("a Vulkan version"),
+                    ).into(),
+                );
+            }
+            (Env::OpenGl, Some(_)) => {
+                return Err(
+                    ErrorKind::UnsupportedByTarget(
+                        String::from("Vulkan version"),
+                        String::from("Vulkan environment"),
+                    ).into(),
+                );
+            }
+            // GL_ARB_gl_spirv only consumes SPIR-V 1.0.
+            (Env::OpenGl, None) => (1, 0),
+        };
+
+        if self.spirv_version > max {
+            let (major, minor) = self.spirv_version;
+
+            let requires = match self.vulkan_version {
+                Some((v_major, v_minor)) => format!("newer than Vulkan {}.{}", v_major, v_minor),
+                None => String::from("Vulkan"),
+            };
+
+            return Err(
+                ErrorKind::UnsupportedByTarget(format!("SPIR-V {}.{}", major, minor), requires)
+                    .into(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Check if the target supports at least the given version of SPIR-V.
+    pub fn supports_spirv(&self, major: u8, minor: u8) -> bool {
+        self.spirv_version >= (major, minor)
+    }
+
+    /// Check if the shader may declare the given extension.
+    pub fn allows_extension(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|e| e == extension)
+    }
+
+    /// Version of SPIR-V, as used in the header of a module.
+    pub(crate) fn version_word(&self) -> u32 {
+        let (major, minor) = self.spirv_version;
+        (major as u32) << 16 | (minor as u32) << 8
+    }
+}
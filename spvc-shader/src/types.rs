@@ -4,10 +4,11 @@ use super::op_key::OpKey;
 use super::rspirv::mr::Operand;
 use super::shader::Shader;
 use super::spirv::{Decoration, Word};
-use super::spirv_type::SpirvType;
+use super::spirv_type::{SpirvType, TypeKey};
 use super::struct_member::StructMember;
 use super::vector_dims::VectorDims;
-use std::rc::Rc;
+use std::borrow::Cow;
+use std::sync::Arc;
 
 /// Types which are used as fields for automatic structs.
 pub mod st {
@@ -29,7 +30,7 @@ pub mod st {
 
 /// Corresponds to the GLSL type vec2.
 pub fn vec2() -> Vector {
-    Vector::new(Float, 3)
+    Vector::new(Float, 2)
 }
 
 /// Corresponds to the GLSL type vec3.
@@ -65,10 +66,6 @@ impl SpirvType for Float {
         4
     }
 
-    fn matches(&self, other: &SpirvType) -> bool {
-        other.as_float().is_some()
-    }
-
     fn as_float(&self) -> Option<Float> {
         Some(*self)
     }
@@ -76,8 +73,14 @@ impl SpirvType for Float {
     fn display(&self) -> String {
         String::from("float")
     }
+
+    #[cfg(feature = "vulkan")]
+    fn as_vulkano_format(&self) -> Option<::vulkano::format::Format> {
+        Some(::vulkano::format::Format::R32Sfloat)
+    }
 }
 
+/// Reflects a 32-bit unsigned integer.
 #[derive(Debug, Clone, Copy)]
 pub struct UnsignedInteger;
 
@@ -92,10 +95,6 @@ impl SpirvType for UnsignedInteger {
         4
     }
 
-    fn matches(&self, other: &SpirvType) -> bool {
-        other.as_unsigned_integer().is_some()
-    }
-
     fn as_unsigned_integer(&self) -> Option<UnsignedInteger> {
         Some(*self)
     }
@@ -103,6 +102,11 @@ impl SpirvType for UnsignedInteger {
     fn display(&self) -> String {
         String::from("uint32_t")
     }
+
+    #[cfg(feature = "vulkan")]
+    fn as_vulkano_format(&self) -> Option<::vulkano::format::Format> {
+        Some(::vulkano::format::Format::R32Uint)
+    }
 }
 
 /// Reflects a boolean value.
@@ -118,10 +122,6 @@ impl SpirvType for Bool {
         4
     }
 
-    fn matches(&self, other: &SpirvType) -> bool {
-        other.as_bool().is_some()
-    }
-
     fn as_bool(&self) -> Option<Bool> {
         Some(*self)
     }
@@ -135,7 +135,7 @@ impl SpirvType for Bool {
 #[derive(Debug, Clone)]
 pub struct Vector {
     /// Component type of the vector.
-    pub component: Rc<SpirvType>,
+    pub component: Arc<SpirvType>,
     /// Component count of the vector.
     pub component_count: u32,
 }
@@ -144,7 +144,7 @@ impl Vector {
     /// Create a new vector.
     pub fn new<T: 'static + SpirvType>(component: T, component_count: u32) -> Vector {
         Vector {
-            component: Rc::new(component),
+            component: Arc::new(component),
             component_count: component_count,
         }
     }
@@ -171,15 +171,6 @@ impl SpirvType for Vector {
         Some(self.component_count)
     }
 
-    fn matches(&self, other: &SpirvType) -> bool {
-        if let Some(other) = other.as_vector() {
-            self.component.matches(other.component.as_ref()) &&
-                self.component_count == other.component_count
-        } else {
-            false
-        }
-    }
-
     fn as_vector_dims(&self) -> Option<VectorDims> {
         Some(VectorDims::new(self.component_count))
     }
@@ -210,6 +201,20 @@ impl SpirvType for Vector {
             }
         }
 
+        if self.component.as_unsigned_integer().is_some() {
+            if self.component_count == 2 {
+                return Some(R32G32Uint);
+            }
+
+            if self.component_count == 3 {
+                return Some(R32G32B32Uint);
+            }
+
+            if self.component_count == 4 {
+                return Some(R32G32B32A32Uint);
+            }
+        }
+
         None
     }
 }
@@ -218,7 +223,7 @@ impl SpirvType for Vector {
 #[derive(Debug, Clone)]
 pub struct Matrix {
     /// Column type of the matrix.
-    pub column_type: Rc<SpirvType>,
+    pub column_type: Arc<SpirvType>,
     /// Column count of the matrix.
     pub column_count: u32,
 }
@@ -227,7 +232,7 @@ impl Matrix {
     /// Create a new matrix.
     pub fn new<T: 'static + SpirvType>(column_type: T, column_count: u32) -> Matrix {
         Matrix {
-            column_type: Rc::new(column_type),
+            column_type: Arc::new(column_type),
             column_count: column_count,
         }
     }
@@ -286,13 +291,9 @@ impl SpirvType for Matrix {
         self.column_type.width() * self.column_count
     }
 
-    fn matches(&self, other: &SpirvType) -> bool {
-        if let Some(other) = other.as_matrix() {
-            self.column_type.matches(other.column_type.as_ref()) &&
-                self.column_count == other.column_count
-        } else {
-            false
-        }
+    /// Each column of a matrix occupies its own location.
+    fn location_slots(&self) -> u32 {
+        self.column_type.location_slots() * self.column_count
     }
 
     fn as_matrix_dims(&self) -> Option<MatrixDims> {
@@ -311,12 +312,27 @@ impl SpirvType for Matrix {
 }
 
 /// Reflects a struct of non-uniform member types.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Struct {
-    /// Name of the struct. Must be unique.
-    pub name: &'static str,
+    /// Name of the struct.
+    ///
+    /// Structs with the same name and members are the same type.
+    pub name: Cow<'static, str>,
     /// Members of the struct.
-    pub members: Vec<Rc<StructMember>>,
+    pub members: Vec<Arc<StructMember>>,
+}
+
+impl Struct {
+    /// Create a new struct, with a name which may be constructed at runtime.
+    pub fn new<N>(name: N, members: Vec<Arc<StructMember>>) -> Struct
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        Struct {
+            name: name.into(),
+            members: members,
+        }
+    }
 }
 
 impl SpirvType for Struct {
@@ -328,21 +344,18 @@ impl SpirvType for Struct {
         }
 
         shader.cache_op(
-            OpKey::Struct {
-                name: String::from(self.name),
-                field_types: field_types.clone(),
-            },
+            OpKey::Struct { key: self.key() },
             |s| {
                 let id = s.builder.type_struct(&field_types);
 
-                s.name(id, self.name);
+                s.name(id, &self.name);
 
                 let mut offset = 0u32;
 
                 for (index, ref m) in self.members.iter().enumerate() {
                     let index = index as u32;
 
-                    s.member_name(id, index, m.name);
+                    s.member_name(id, index, &m.name);
 
                     s.builder.member_decorate(
                         id,
@@ -365,25 +378,13 @@ impl SpirvType for Struct {
         self.members.iter().map(|m| m.ty.width()).sum()
     }
 
-    fn matches(&self, other: &SpirvType) -> bool {
-        if let Some(other) = other.as_struct() {
-            if self.name != other.name {
-                return false;
-            }
-
-            let mut a = self.members.iter();
-            let mut b = other.members.iter();
-
-            while let (Some(a), Some(b)) = (a.next(), b.next()) {
-                if !a.matches(b) {
-                    return false;
-                }
-            }
+    fn as_struct(&self) -> Option<Struct> {
+        Some(self.clone())
+    }
 
-            return a.next().is_none() && b.next().is_none();
-        } else {
-            false
-        }
+    fn key(&self) -> TypeKey {
+        let members = self.members.iter().map(|m| (m.name.clone(), m.ty.key())).collect();
+        TypeKey::Struct(self.name.clone(), members)
     }
 
     fn display(&self) -> String {
@@ -1,5 +1,6 @@
 use super::errors::*;
 use super::interface::Interface;
+use super::node::Node;
 use super::op::Op;
 use super::op_key::OpKey;
 use super::pointer::Pointer;
@@ -8,7 +9,7 @@ use super::rspirv::mr::Operand;
 use super::shader::Shader;
 use super::spirv::{Decoration, StorageClass};
 use super::spirv_type::{SpirvType, WrapperType};
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Reflection of a uniform global variable.
 #[derive(Debug)]
@@ -35,13 +36,17 @@ impl Op for UniformVar {
     }
 
     fn storage_class(&self) -> Option<StorageClass> {
-        Some(StorageClass::Uniform)
+        Some(self.pointer.storage_class())
     }
 
     fn op_type(&self) -> &SpirvType {
         &self.pointer
     }
 
+    fn node(&self) -> Node {
+        Node::Uniform(self)
+    }
+
     fn register_op(&self, shader: &mut Shader) -> Result<Box<RegOp>> {
         let variable_type = self.pointer.register_type(shader)?;
 
@@ -55,7 +60,7 @@ impl Op for UniformVar {
                 let variable_id = s.builder.variable(
                     variable_type,
                     None,
-                    StorageClass::Uniform,
+                    self.pointer.storage_class(),
                     None,
                 );
 
@@ -88,15 +93,48 @@ impl UniformVar {
         ty: T,
         set: u32,
         binding: u32,
-    ) -> Rc<UniformVar> {
-        Rc::new(UniformVar {
+    ) -> Arc<UniformVar> {
+        UniformVar::from_type(name, Arc::new(ty), set, binding)
+    }
+
+    /// Construct a new storage buffer variable.
+    ///
+    /// Requires a target supporting SPIR-V 1.3, or allowing the
+    /// `SPV_KHR_storage_buffer_storage_class` extension.
+    pub fn storage_buffer<T: 'static + SpirvType>(
+        name: &str,
+        ty: T,
+        set: u32,
+        binding: u32,
+    ) -> Arc<UniformVar> {
+        Arc::new(UniformVar {
             name: String::from(name),
-            pointer: Pointer::new(StorageClass::Uniform, Rc::new(ty)),
+            pointer: Pointer::new(StorageClass::StorageBuffer, Arc::new(ty)),
             set: set,
             binding: binding,
         })
     }
 
+    /// Construct a new uniform variable, with an already shared type.
+    pub(crate) fn from_type(
+        name: &str,
+        ty: Arc<SpirvType>,
+        set: u32,
+        binding: u32,
+    ) -> Arc<UniformVar> {
+        Arc::new(UniformVar {
+            name: String::from(name),
+            pointer: Pointer::new(StorageClass::Uniform, ty),
+            set: set,
+            binding: binding,
+        })
+    }
+
+    /// Check if this is a storage buffer, rather than a uniform buffer.
+    pub fn is_storage_buffer(&self) -> bool {
+        self.pointer.storage_class() == StorageClass::StorageBuffer
+    }
+
     /// Setup a vulkan descriptor for this uniform variable.
     #[cfg(feature = "vulkan")]
     pub fn as_vulkan_descriptor(
@@ -109,11 +147,11 @@ impl UniformVar {
         Some(DescriptorDesc {
             ty: DescriptorDescTy::Buffer(DescriptorBufferDesc {
                 dynamic: Some(false),
-                storage: false,
+                storage: self.is_storage_buffer(),
             }),
             array_count: 1,
             stages: stages.clone(),
-            readonly: true,
+            readonly: !self.is_storage_buffer(),
         })
     }
 }
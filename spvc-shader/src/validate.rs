@@ -0,0 +1,663 @@
+//! # Validation of SPIR-V modules
+//!
+//! Checks a module produced by [`Shader::module`](../struct.Shader.html#method.module) against the
+//! most common rules that are broken when building shaders, without requiring `spirv-val`.
+
+use super::errors::*;
+use super::rspirv::mr::{Instruction, Module, Operand};
+use super::spirv::{self, Decoration, ExecutionMode, ExecutionModel, StorageClass, Word};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A single rule violated by a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Two entry points have the same name and execution model.
+    DuplicateEntryPoint {
+        /// Name of the entry points.
+        entry_point: String,
+        /// Execution model of the entry points.
+        model: ExecutionModel,
+    },
+    /// A fragment entry point has no `OriginUpperLeft` or `OriginLowerLeft` execution mode.
+    MissingOriginMode {
+        /// Name of the entry point.
+        entry_point: String,
+    },
+    /// An input or output variable is used by an entry point, but missing from its interface.
+    MissingInterfaceVariable {
+        /// Name of the entry point.
+        entry_point: String,
+        /// Id of the variable.
+        variable: Word,
+    },
+    /// An id is used before it is declared.
+    ForwardReference {
+        /// Id of the instruction using the id.
+        user: Option<Word>,
+        /// The id being referenced.
+        id: Word,
+    },
+    /// A struct used in a uniform or storage buffer variable is not decorated with `Block` or
+    /// `BufferBlock`.
+    MissingBlockDecoration {
+        /// Id of the struct type.
+        ty: Word,
+    },
+    /// A member of a block is missing an `Offset` decoration.
+    MissingMemberOffset {
+        /// Id of the struct type.
+        ty: Word,
+        /// Index of the member.
+        member: u32,
+    },
+    /// A type is declared more than once.
+    DuplicateType {
+        /// Id of the first declaration.
+        first: Word,
+        /// Id of the duplicate declaration.
+        duplicate: Word,
+    },
+    /// A basic block does not end with a termination instruction.
+    UnterminatedBlock {
+        /// Id of the label of the block.
+        label: Option<Word>,
+    },
+    /// A termination instruction is followed by other instructions in the same block.
+    TerminatorNotLast {
+        /// Id of the label of the block.
+        label: Option<Word>,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        use self::ValidationError::*;
+
+        match *self {
+            DuplicateEntryPoint {
+                ref entry_point,
+                model,
+            } => write!(fmt, "{:?} entry point `{}` is declared twice", model, entry_point),
+            MissingOriginMode { ref entry_point } => {
+                write!(
+                    fmt,
+                    "fragment entry point `{}` has no origin execution mode",
+                    entry_point
+                )
+            }
+            MissingInterfaceVariable {
+                ref entry_point,
+                variable,
+            } => {
+                write!(
+                    fmt,
+                    "variable %{} is used by `{}`, but is not part of its interface",
+                    variable,
+                    entry_point
+                )
+            }
+            ForwardReference { user, id } => {
+                match user {
+                    Some(user) => {
+                        write!(fmt, "%{} references %{} before it is declared", user, id)
+                    }
+                    None => write!(fmt, "%{} is referenced before it is declared", id),
+                }
+            }
+            MissingBlockDecoration { ty } => {
+                write!(fmt, "buffer struct %{} is not decorated with Block", ty)
+            }
+            MissingMemberOffset { ty, member } => {
+                write!(fmt, "member {} of block %{} has no Offset decoration", member, ty)
+            }
+            DuplicateType { first, duplicate } => {
+                write!(fmt, "type %{} is a duplicate of %{}", duplicate, first)
+            }
+            UnterminatedBlock { label } => {
+                write!(fmt, "block {} does not end with a terminator", display_label(label))
+            }
+            TerminatorNotLast { label } => {
+                write!(
+                    fmt,
+                    "block {} has instructions after its terminator",
+                    display_label(label)
+                )
+            }
+        }
+    }
+}
+
+fn display_label(label: Option<Word>) -> String {
+    label.map(|l| format!("%{}", l)).unwrap_or_else(
+        || String::from("<unlabeled>"),
+    )
+}
+
+/// Validate the given module, failing with all rules that are violated.
+pub fn validate(module: &Module) -> Result<()> {
+    let errors = check(module);
+
+    if !errors.is_empty() {
+        return Err(ErrorKind::Invalid(errors).into());
+    }
+
+    Ok(())
+}
+
+/// Check the given module, returning all rules that are violated.
+pub fn check(module: &Module) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    check_entry_points(module, &mut errors);
+    check_execution_modes(module, &mut errors);
+    check_interfaces(module, &mut errors);
+    check_ordering(module, &mut errors);
+    check_blocks(module, &mut errors);
+    check_duplicate_types(module, &mut errors);
+    check_termination(module, &mut errors);
+
+    errors
+}
+
+fn id_operands(inst: &Instruction) -> Vec<Word> {
+    inst.operands
+        .iter()
+        .filter_map(|o| match *o {
+            Operand::IdRef(id) => Some(id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Entry points of the module, as execution model, function id, name and interface.
+fn entry_points(module: &Module) -> Vec<(ExecutionModel, Word, String, Vec<Word>)> {
+    let mut out = Vec::new();
+
+    for inst in &module.entry_points {
+        let model = match inst.operands.get(0) {
+            Some(&Operand::ExecutionModel(model)) => model,
+            _ => continue,
+        };
+
+        let function = match inst.operands.get(1) {
+            Some(&Operand::IdRef(id)) => id,
+            _ => continue,
+        };
+
+        let name = match inst.operands.get(2) {
+            Some(&Operand::LiteralString(ref name)) => name.clone(),
+            _ => continue,
+        };
+
+        let interface = inst.operands[3..]
+            .iter()
+            .filter_map(|o| match *o {
+                Operand::IdRef(id) => Some(id),
+                _ => None,
+            })
+            .collect();
+
+        out.push((model, function, name, interface));
+    }
+
+    out
+}
+
+fn check_entry_points(module: &Module, errors: &mut Vec<ValidationError>) {
+    let mut seen = HashSet::new();
+
+    for (model, _, name, _) in entry_points(module) {
+        if !seen.insert((model, name.clone())) {
+            errors.push(ValidationError::DuplicateEntryPoint {
+                entry_point: name,
+                model: model,
+            });
+        }
+    }
+}
+
+fn check_execution_modes(module: &Module, errors: &mut Vec<ValidationError>) {
+    for (model, function, name, _) in entry_points(module) {
+        if model != ExecutionModel::Fragment {
+            continue;
+        }
+
+        let has_origin = module.execution_modes.iter().any(|inst| {
+            inst.operands.get(0) == Some(&Operand::IdRef(function)) &&
+                match inst.operands.get(1) {
+                    Some(&Operand::ExecutionMode(ExecutionMode::OriginUpperLeft)) |
+                    Some(&Operand::ExecutionMode(ExecutionMode::OriginLowerLeft)) => true,
+                    _ => false,
+                }
+        });
+
+        if !has_origin {
+            errors.push(ValidationError::MissingOriginMode { entry_point: name });
+        }
+    }
+}
+
+fn check_interfaces(module: &Module, errors: &mut Vec<ValidationError>) {
+    // global input and output variables.
+    let mut variables = HashSet::new();
+
+    for inst in &module.types_global_values {
+        if inst.class.opcode != spirv::Op::Variable {
+            continue;
+        }
+
+        match inst.operands.get(0) {
+            Some(&Operand::StorageClass(StorageClass::Input)) |
+            Some(&Operand::StorageClass(StorageClass::Output)) => {}
+            _ => continue,
+        }
+
+        if let Some(id) = inst.result_id {
+            variables.insert(id);
+        }
+    }
+
+    for (_, function, name, interface) in entry_points(module) {
+        let function = module.functions.iter().find(|f| {
+            f.def.as_ref().and_then(|d| d.result_id) == Some(function)
+        });
+
+        let function = match function {
+            Some(function) => function,
+            None => continue,
+        };
+
+        let mut reported = HashSet::new();
+
+        for block in &function.basic_blocks {
+            for inst in &block.instructions {
+                for id in id_operands(inst) {
+                    if variables.contains(&id) && !interface.contains(&id) && reported.insert(id) {
+                        errors.push(ValidationError::MissingInterfaceVariable {
+                            entry_point: name.clone(),
+                            variable: id,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn check_ordering(module: &Module, errors: &mut Vec<ValidationError>) {
+    let mut declared = HashSet::new();
+
+    // strings are declared before types, and may be used by non-semantic instructions.
+    for inst in module.ext_inst_imports.iter().chain(module.debugs.iter()) {
+        declared.extend(inst.result_id);
+    }
+
+    for inst in &module.types_global_values {
+        let references = inst.result_type.into_iter().chain(id_operands(inst));
+
+        for id in references {
+            if !declared.contains(&id) {
+                errors.push(ValidationError::ForwardReference {
+                    user: inst.result_id,
+                    id: id,
+                });
+            }
+        }
+
+        declared.extend(inst.result_id);
+    }
+}
+
+fn has_decoration(module: &Module, id: Word, decoration: Decoration) -> bool {
+    module.annotations.iter().any(|inst| {
+        inst.class.opcode == spirv::Op::Decorate &&
+            inst.operands.get(0) == Some(&Operand::IdRef(id)) &&
+            inst.operands.get(1) == Some(&Operand::Decoration(decoration))
+    })
+}
+
+fn has_member_decoration(module: &Module, id: Word, member: u32, decoration: Decoration) -> bool {
+    module.annotations.iter().any(|inst| {
+        inst.class.opcode == spirv::Op::MemberDecorate &&
+            inst.operands.get(0) == Some(&Operand::IdRef(id)) &&
+            inst.operands.get(1) == Some(&Operand::LiteralInt32(member)) &&
+            inst.operands.get(2) == Some(&Operand::Decoration(decoration))
+    })
+}
+
+fn check_blocks(module: &Module, errors: &mut Vec<ValidationError>) {
+    let structs: HashMap<Word, &Instruction> = module
+        .types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::TypeStruct)
+        .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+        .collect();
+
+    // pointee of each pointer type.
+    let pointers: HashMap<Word, Word> = module
+        .types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::TypePointer)
+        .filter_map(|inst| match (inst.result_id, inst.operands.get(1)) {
+            (Some(id), Some(&Operand::IdRef(pointee))) => Some((id, pointee)),
+            _ => None,
+        })
+        .collect();
+
+    let mut checked = HashSet::new();
+
+    // only the outermost struct of a variable is a block, and pointers to nested structs are
+    // created by access chains.
+    for inst in &module.types_global_values {
+        if inst.class.opcode != spirv::Op::Variable {
+            continue;
+        }
+
+        let storage_class = match inst.operands.get(0) {
+            Some(&Operand::StorageClass(storage_class @ StorageClass::Uniform)) |
+            Some(&Operand::StorageClass(storage_class @ StorageClass::StorageBuffer)) => {
+                storage_class
+            }
+            _ => continue,
+        };
+
+        let pointee = match inst.result_type.and_then(|t| pointers.get(&t)) {
+            Some(&pointee) => pointee,
+            None => continue,
+        };
+
+        let st = match structs.get(&pointee) {
+            Some(st) => st,
+            None => continue,
+        };
+
+        if !checked.insert(pointee) {
+            continue;
+        }
+
+        // `BufferBlock` is only used for storage buffers in the `Uniform` storage class.
+        if !has_decoration(module, pointee, Decoration::Block) &&
+            (storage_class != StorageClass::Uniform ||
+                 !has_decoration(module, pointee, Decoration::BufferBlock))
+        {
+            errors.push(ValidationError::MissingBlockDecoration { ty: pointee });
+        }
+
+        for member in 0..st.operands.len() as u32 {
+            if !has_member_decoration(module, pointee, member, Decoration::Offset) {
+                errors.push(ValidationError::MissingMemberOffset {
+                    ty: pointee,
+                    member: member,
+                });
+            }
+        }
+    }
+}
+
+fn check_duplicate_types(module: &Module, errors: &mut Vec<ValidationError>) {
+    // structs, pointers and arrays are allowed to be duplicated, since they might be decorated
+    // differently.
+    let mut seen: Vec<&Instruction> = Vec::new();
+
+    for inst in &module.types_global_values {
+        let opcode = inst.class.opcode;
+
+        if !inst.class.opname.starts_with("Type") {
+            continue;
+        }
+
+        match opcode {
+            spirv::Op::TypeStruct |
+            spirv::Op::TypePointer |
+            spirv::Op::TypeArray |
+            spirv::Op::TypeRuntimeArray => continue,
+            _ => {}
+        }
+
+        let first = seen.iter().find(|other| {
+            other.class.opcode == opcode && other.operands == inst.operands
+        });
+
+        match (first.and_then(|f| f.result_id), inst.result_id) {
+            (Some(first), Some(duplicate)) => {
+                errors.push(ValidationError::DuplicateType {
+                    first: first,
+                    duplicate: duplicate,
+                });
+            }
+            _ => seen.push(inst),
+        }
+    }
+}
+
+fn is_terminator(opcode: spirv::Op) -> bool {
+    match opcode {
+        spirv::Op::Branch |
+        spirv::Op::BranchConditional |
+        spirv::Op::Switch |
+        spirv::Op::Return |
+        spirv::Op::ReturnValue |
+        spirv::Op::Kill |
+        spirv::Op::Unreachable => true,
+        _ => false,
+    }
+}
+
+fn check_termination(module: &Module, errors: &mut Vec<ValidationError>) {
+    for function in &module.functions {
+        for block in &function.basic_blocks {
+            let label = block.label.as_ref().and_then(|l| l.result_id);
+
+            match block.instructions.last() {
+                Some(last) if is_terminator(last.class.opcode) => {}
+                _ => errors.push(ValidationError::UnterminatedBlock { label: label }),
+            }
+
+            let len = block.instructions.len();
+
+            if block.instructions.iter().take(len.saturating_sub(1)).any(|inst| {
+                is_terminator(inst.class.opcode)
+            })
+            {
+                errors.push(ValidationError::TerminatorNotLast { label: label });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValidationError::*;
+    use super::check;
+    use rspirv::mr::{Builder, Instruction, Module, Operand};
+    use spirv::{self, Decoration, ExecutionMode, ExecutionModel, StorageClass, Word};
+
+    /// Build a function returning void, with a single block containing the given body.
+    fn function<F: FnOnce(&mut Builder)>(b: &mut Builder, body: F) -> Word {
+        let void = b.type_void();
+        let fn_type = b.type_function(void, &[]);
+        let id = b.begin_function(void, None, spirv::FUNCTION_CONTROL_NONE, fn_type)
+            .unwrap();
+        b.begin_basic_block(None).unwrap();
+        body(b);
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        id
+    }
+
+    /// Build a module with a single vertex entry point.
+    fn vertex<F: FnOnce(&mut Builder)>(declare: F) -> Module {
+        let mut b = Builder::new();
+        declare(&mut b);
+        let main = function(&mut b, |_| {});
+        b.entry_point(ExecutionModel::Vertex, main, String::from("main"), &[]);
+        b.module()
+    }
+
+    fn label(module: &Module) -> Option<Word> {
+        module.functions[0].basic_blocks[0].label.as_ref().and_then(|l| l.result_id)
+    }
+
+    #[test]
+    fn test_valid() {
+        assert_eq!(check(&vertex(|_| {})), vec![]);
+    }
+
+    #[test]
+    fn test_duplicate_entry_point() {
+        let mut b = Builder::new();
+        let main = function(&mut b, |_| {});
+        b.entry_point(ExecutionModel::Vertex, main, String::from("main"), &[]);
+        b.entry_point(ExecutionModel::Vertex, main, String::from("main"), &[]);
+
+        assert_eq!(
+            check(&b.module()),
+            vec![
+                DuplicateEntryPoint {
+                    entry_point: String::from("main"),
+                    model: ExecutionModel::Vertex,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_origin_mode() {
+        let mut b = Builder::new();
+        let main = function(&mut b, |_| {});
+        b.entry_point(ExecutionModel::Fragment, main, String::from("main"), &[]);
+        assert_eq!(
+            check(&b.module()),
+            vec![MissingOriginMode { entry_point: String::from("main") }]
+        );
+
+        let mut b = Builder::new();
+        let main = function(&mut b, |_| {});
+        b.entry_point(ExecutionModel::Fragment, main, String::from("main"), &[]);
+        b.execution_mode(main, ExecutionMode::OriginUpperLeft, &[]);
+        assert_eq!(check(&b.module()), vec![]);
+    }
+
+    #[test]
+    fn test_missing_interface_variable() {
+        let mut b = Builder::new();
+        let float = b.type_float(32);
+        let pointer = b.type_pointer(None, StorageClass::Input, float);
+        let variable = b.variable(pointer, None, StorageClass::Input, None);
+
+        let main = function(&mut b, |b| { b.load(float, None, variable, None, &[]).unwrap(); });
+        b.entry_point(ExecutionModel::Vertex, main, String::from("main"), &[]);
+
+        assert_eq!(
+            check(&b.module()),
+            vec![
+                MissingInterfaceVariable {
+                    entry_point: String::from("main"),
+                    variable: variable,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_forward_reference() {
+        let mut float = 0;
+        let mut vector = 0;
+
+        let mut module = vertex(|b| {
+            float = b.type_float(32);
+            vector = b.type_vector(float, 4);
+        });
+
+        module.types_global_values.swap(0, 1);
+
+        assert_eq!(
+            check(&module),
+            vec![
+                ForwardReference {
+                    user: Some(vector),
+                    id: float,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_block_decoration() {
+        let mut ty = 0;
+
+        let module = vertex(|b| {
+            let float = b.type_float(32);
+            ty = b.type_struct(&[float]);
+            let pointer = b.type_pointer(None, StorageClass::Uniform, ty);
+            b.variable(pointer, None, StorageClass::Uniform, None);
+            b.member_decorate(ty, 0, Decoration::Offset, &[Operand::LiteralInt32(0)]);
+        });
+
+        assert_eq!(check(&module), vec![MissingBlockDecoration { ty: ty }]);
+    }
+
+    #[test]
+    fn test_missing_member_offset() {
+        let mut ty = 0;
+
+        let module = vertex(|b| {
+            let float = b.type_float(32);
+            ty = b.type_struct(&[float, float]);
+            let pointer = b.type_pointer(None, StorageClass::Uniform, ty);
+            b.variable(pointer, None, StorageClass::Uniform, None);
+            b.decorate(ty, Decoration::Block, &[]);
+            b.member_decorate(ty, 0, Decoration::Offset, &[Operand::LiteralInt32(0)]);
+        });
+
+        assert_eq!(check(&module), vec![MissingMemberOffset { ty: ty, member: 1 }]);
+    }
+
+    #[test]
+    fn test_duplicate_type() {
+        let mut first = 0;
+        let mut duplicate = 0;
+
+        let mut module = vertex(|b| {
+            first = b.type_float(32);
+            duplicate = b.id();
+        });
+
+        module.types_global_values.push(Instruction::new(
+            spirv::Op::TypeFloat,
+            None,
+            Some(duplicate),
+            vec![Operand::LiteralInt32(32)],
+        ));
+
+        assert_eq!(
+            check(&module),
+            vec![
+                DuplicateType {
+                    first: first,
+                    duplicate: duplicate,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_block() {
+        let mut module = vertex(|_| {});
+        module.functions[0].basic_blocks[0].instructions.pop();
+        assert_eq!(check(&module), vec![UnterminatedBlock { label: label(&module) }]);
+    }
+
+    #[test]
+    fn test_terminator_not_last() {
+        let mut module = vertex(|_| {});
+
+        module.functions[0].basic_blocks[0].instructions.insert(
+            0,
+            Instruction::new(spirv::Op::Return, None, None, vec![]),
+        );
+
+        assert_eq!(check(&module), vec![TerminatorNotLast { label: label(&module) }]);
+    }
+}
@@ -18,7 +18,48 @@ pub struct Global {
     projection: st::Mat4,
 }
 
+/// Optional features of the PBR shaders, used to build permutations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Features {
+    /// Pass normals through to the fragment stage.
+    pub normals: bool,
+    /// Pass texture coordinates through to the fragment stage.
+    pub tex_coords: bool,
+}
+
+impl Default for Features {
+    fn default() -> Features {
+        Features {
+            normals: true,
+            tex_coords: true,
+        }
+    }
+}
+
+impl Features {
+    /// Names of all features, as used by `set`.
+    pub const NAMES: &'static [&'static str] = &["normals", "tex_coords"];
+
+    /// Enable or disable the feature with the given name.
+    ///
+    /// Returns `false` if there is no such feature.
+    pub fn set(&mut self, name: &str, enabled: bool) -> bool {
+        match name {
+            "normals" => self.normals = enabled,
+            "tex_coords" => self.tex_coords = enabled,
+            _ => return false,
+        }
+
+        true
+    }
+}
+
 pub fn vertex_shader() -> Result<Shader> {
+    vertex_shader_with(Features::default())
+}
+
+/// Build the vertex shader, with the given features.
+pub fn vertex_shader_with(features: Features) -> Result<Shader> {
     let mut shader = Shader::new();
 
     let model = UniformVar::new("model", Model::type_info(), 0, 0);
@@ -51,22 +92,29 @@ pub fn vertex_shader() -> Result<Shader> {
         let pos = mul(load(global.access_member(Global::projection())), pos);
 
         main.op(store(gl_position.clone(), pos));
-        main.op(store(v_tex_coord.clone(), load(tex_coord.clone())));
-        main.op(store(v_normal.clone(), load(normal.clone())));
-
-        shader.entry_point(
-            ShaderKind::Vertex,
-            main.returns_void(),
-            vec![
-                position.clone(),
-                normal.clone(),
-                tex_coord.clone(),
-                gl_position.clone(),
-                v_normal.clone(),
-                v_tex_coord.clone(),
-            ],
-        )?;
+
+        if features.tex_coords {
+            main.op(store(v_tex_coord.clone(), load(tex_coord.clone())));
+        }
+
+        if features.normals {
+            main.op(store(v_normal.clone(), load(normal.clone())));
+        }
+
+        shader.entry_point_auto(ShaderKind::Vertex, main.returns_void())?;
     }
 
     Ok(shader)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spvc_shader::validate;
+
+    #[test]
+    fn test_vertex_shader_is_valid() {
+        let module = vertex_shader().unwrap().module();
+        assert_eq!(validate::check(&module), vec![]);
+    }
+}
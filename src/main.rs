@@ -1,29 +1,216 @@
 extern crate rspirv;
+extern crate serde_json;
+extern crate spvc_shader;
 extern crate spvc_shaders;
 
 use self::rspirv::binary::Assemble;
 use self::rspirv::binary::Disassemble;
+use self::rspirv::mr::Module;
+use spvc_shader::{Shader, compare, optimize, reflect};
 use spvc_shaders::pbr;
+use std::env;
+use std::error::Error;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, Read, Write};
+use std::process;
 use std::slice;
 
-fn main() {
-    let module = pbr::vertex_shader().unwrap().module();
+const USAGE: &'static str = "usage: spvc <command> [<args>]
+
+commands:
+    build <shader> [-o <file>] [--enable <feature>] [--disable <feature>] [-O] [--strip-names]
+        Build a prebuilt shader, and write it to <file> (default: out.spv).
+        -O merges common subexpressions and removes unused globals, --strip-names also removes
+        all debug names.
+    dis <file>
+        Print the disassembly of a SPIR-V binary.
+    reflect <file> [--json]
+        Print the reflected entry points of a SPIR-V binary.
+    diff <expected> <actual>
+        Compare two SPIR-V binaries structurally, exiting with 1 if they differ.
+
+shaders:
+    pbr.vert";
+
+type Result<T> = ::std::result::Result<T, Box<Error>>;
+
+/// Build a prebuilt shader with the given name and feature flags.
+fn build_shader(name: &str, flags: &[(String, bool)]) -> Result<Shader> {
+    match name {
+        "pbr.vert" => {
+            let mut features = pbr::Features::default();
+
+            for &(ref feature, enabled) in flags {
+                if !features.set(feature, enabled) {
+                    return Err(
+                        format!(
+                            "{}: no feature `{}`, expected one of: {}",
+                            name,
+                            feature,
+                            pbr::Features::NAMES.join(", ")
+                        ).into(),
+                    );
+                }
+            }
 
-    let code = module.assemble();
-    assert!(code.len() > 20);
+            Ok(pbr::vertex_shader_with(features)?)
+        }
+        _ => Err(format!("no shader named `{}`", name).into()),
+    }
+}
+
+fn build(args: &[String]) -> Result<()> {
+    let mut shader = None;
+    let mut output = String::from("out.spv");
+    let mut flags = Vec::new();
+    let mut optimized = false;
+    let mut options = optimize::Options::default();
+
+    let mut it = args.iter();
+
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "-o" => output = it.next().ok_or("-o: missing argument")?.clone(),
+            "-O" => optimized = true,
+            "--strip-names" => {
+                optimized = true;
+                options.strip_names = true;
+            }
+            "--enable" => {
+                flags.push((it.next().ok_or("--enable: missing argument")?.clone(), true))
+            }
+            "--disable" => {
+                flags.push((it.next().ok_or("--disable: missing argument")?.clone(), false))
+            }
+            _ if shader.is_none() => shader = Some(arg.as_str()),
+            _ => return Err(format!("unexpected argument `{}`", arg).into()),
+        }
+    }
 
-    let mut loader = self::rspirv::mr::Loader::new();
-    self::rspirv::binary::parse_words(&code, &mut loader).unwrap();
-    let module = loader.module();
+    let shader = shader.ok_or("missing shader name")?;
+    let shader = build_shader(shader, &flags)?;
 
-    let mut out = File::create("out.spv").unwrap();
+    let code = if optimized {
+        shader.optimized_module(options).assemble()
+    } else {
+        shader.module().assemble()
+    };
+
+    let mut out = File::create(&output)?;
 
     unsafe {
         let code = slice::from_raw_parts(code.as_ptr() as *const u8, code.len() * 4);
-        out.write_all(code).unwrap();
+        out.write_all(code)?;
+    }
+
+    Ok(())
+}
+
+fn load(path: &str) -> Result<Module> {
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+    Ok(compare::load_bytes(&bytes)?)
+}
+
+fn dis(args: &[String]) -> Result<()> {
+    match args {
+        &[ref path] => {
+            println!("{}", load(path)?.disassemble());
+            Ok(())
+        }
+        _ => Err("usage: spvc dis <file>".into()),
+    }
+}
+
+fn reflect(args: &[String]) -> Result<()> {
+    let (path, json) = match args {
+        &[ref path] => (path, false),
+        &[ref path, ref flag] if flag == "--json" => (path, true),
+        _ => return Err("usage: spvc reflect <file> [--json]".into()),
+    };
+
+    let entry_points = reflect::from_module(&load(path)?)?;
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if json {
+        serde_json::to_writer_pretty(&mut out, &entry_points)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    for e in &entry_points {
+        writeln!(out, "entry point {} ({:?})", e.name, e.kind)?;
+
+        for v in &e.inputs {
+            writeln!(out, "  input {} location={} {:?}", v.name, v.location, v.ty)?;
+        }
+
+        for v in &e.outputs {
+            writeln!(out, "  output {} location={} {:?}", v.name, v.location, v.ty)?;
+        }
+
+        for set in &e.descriptor_sets {
+            for b in &set.bindings {
+                writeln!(
+                    out,
+                    "  {:?} {} set={} binding={}",
+                    b.kind,
+                    b.name,
+                    set.set,
+                    b.binding
+                )?;
+            }
+        }
+
+        for p in &e.push_constants {
+            writeln!(out, "  push constant {} offset={} size={}", p.name, p.offset, p.size)?;
+        }
+
+        for c in &e.spec_constants {
+            writeln!(out, "  spec constant {} id={} {:?}", c.name, c.id, c.ty)?;
+        }
     }
 
-    println!("{}", module.disassemble());
+    Ok(())
+}
+
+fn diff(args: &[String]) -> Result<()> {
+    let (expected, actual) = match args {
+        &[ref expected, ref actual] => (load(expected)?, load(actual)?),
+        _ => return Err("usage: spvc diff <expected> <actual>".into()),
+    };
+
+    let differences = compare::compare(&expected, &actual);
+
+    for d in &differences {
+        println!("{}", d);
+    }
+
+    if !differences.is_empty() {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = match args.get(0).map(String::as_str) {
+        Some("build") => build(&args[1..]),
+        Some("dis") => dis(&args[1..]),
+        Some("reflect") => reflect(&args[1..]),
+        Some("diff") => diff(&args[1..]),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
 }